@@ -0,0 +1,7 @@
+// The Model's methods mostly return Result<(), ()>, as there's only ever one way for them to fail (e.g. something
+// with the given id doesn't exist) and the caller decides how to report it.
+#![allow(clippy::result_unit_err)]
+
+// The data model lives in a library so that it can be used natively (e.g. by command-line tools and tests),
+// as well as by the web app in main.rs.
+pub mod model;
@@ -1,33 +1,32 @@
 use std::{cell::RefCell, ops::DerefMut};
 
+use tournament_tracker::model;
 use ui::bracket_view::BracketView;
+use ui::chances::Chances;
 use ui::match_list::MatchList;
 use model::Model;
+use model::storage::LocalStorageBackend;
 use ui::outline::Outline;
 use ui::standings::Standings;
 use ui::round_robin_table::RoundRobinTable;
+use ui::swiss_view::SwissView;
+use ui::toolbar::Toolbar;
 use ui::{Ui, UiElement};
 use web_sys::{window};
 
 mod dom;
-mod model;
 mod ui;
 
-//TODO: maybe have a read-only lock toggle, for entering match results vs. viewing stats etc. It's too easy to accidentally click and lose data!
-//TODO: "what-if" mode where can enter potential future results to see what happens,
-//  e.g. percentages of playoffs without permanently changing the already-completed matches
 //TODO: round-robin diagram with arrows (like I draw on paint), useful for smaller groups e.g. 4
 //TODO: import data from lolesports or lol wiki?
 //TODO: highlight teams on mouse hover (synced across all the different UI elements)
-//TODO: shortcut buttons for "preset" brackets, like single-elimination, double-elimination so that you don't have
-//      to manually create all the fixures and lay them out
 
 // We use some global state as callbacks from the Javascript world (e.g. click event handlers)
 // will need the Model (for example), but we can't easily store a reference in the callback closure
 // due to Rust's borrowing rules, as the closures are long-lived and so we would need to use something
 // like reference counting, which makes the rest of the code less ergonomic and more prone to runtime panics.
 thread_local! {
-    static GLOBAL_MODEL: RefCell<Model> = RefCell::new(Model::load());
+    static GLOBAL_MODEL: RefCell<Model> = RefCell::new(load_model());
     static GLOBAL_UI: RefCell<Ui> = RefCell::new(Ui::new());
 }
 
@@ -44,6 +43,20 @@ pub fn with_globals<F: FnOnce(&mut Model, &mut Ui) -> ()>(f: F) {
     });
 }
 
+fn load_model() -> Model {
+    match Model::load(Box::new(LocalStorageBackend::new())) {
+        Ok(m) => m,
+        Err(e) => {
+            if window().unwrap().confirm_with_message(&format!("Failed to load saved data: {:?}! If this is expected then click OK and it will be reset. Otherwise check what's going on.",
+                e)) == Ok(true) {
+                Model::with_storage(Box::new(LocalStorageBackend::new()))
+            } else {
+                panic!("No data!");
+            }
+        }
+    }
+}
+
 fn add_ui_element(ui: &mut Ui, ui_element: UiElement, insertion_selector: &str) {
     window().expect("Missing window")
         .document().expect("Missing document")
@@ -57,6 +70,9 @@ fn main() {
     console_log::init_with_level(log::Level::Debug).expect("Failed to init logging");
 
     with_globals(|model, ui| {
+        let toolbar = Toolbar::new(ui.get_next_id(), model);
+        add_ui_element(ui, UiElement::Toolbar(toolbar), "#toolbar");
+
         let outline = Outline::new(ui.get_next_id(), model);
         let outline_id = outline.get_id();
         add_ui_element(ui, UiElement::Outline(outline), "#left-pane");
@@ -64,9 +80,15 @@ fn main() {
         let match_list = BracketView::new(ui.get_next_id(), model, outline_id);
         add_ui_element(ui, UiElement::BracketView(match_list), "#right-pane");
 
+        let swiss_view = SwissView::new(ui.get_next_id(), model, outline_id);
+        add_ui_element(ui, UiElement::SwissView(swiss_view), "#right-pane");
+
         let standings = Standings::new(ui.get_next_id(), model, outline_id);
         add_ui_element(ui, UiElement::Standings(standings), "#right-pane");
 
+        let chances = Chances::new(ui.get_next_id(), model, outline_id);
+        add_ui_element(ui, UiElement::Chances(chances), "#right-pane");
+
         let table = RoundRobinTable::new(ui.get_next_id(), model, outline_id);
         add_ui_element(ui, UiElement::RoundRobinTable(table), "#right-pane");
 
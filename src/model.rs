@@ -1,26 +1,49 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
 use indexmap::IndexMap;
 use indexmap::indexmap;
 use log::debug;
 use log::error;
 use serde::Deserialize;
 use serde::Serialize;
-use web_sys::Storage;
-use web_sys::window;
 
 use crate::model::tournament::Match;
 use crate::model::tournament::MatchId;
 use crate::model::tournament::Team;
-use crate::ui::Ui;
 use crate::model::{tournament::{TournamentId, Tournament, StageId, Stage, TeamId}};
 
+use self::tournament::BracketExit;
 use self::tournament::Fixture;
 use self::tournament::FixtureId;
 use self::tournament::FixtureInput;
 use self::tournament::FixtureTeam;
+use self::tournament::Outcome;
+use self::tournament::ScoringSystem;
+use self::tournament::SeriesFormat;
+use self::tournament::ScheduledMatch;
 use self::tournament::StageKind;
-
+use self::tournament::SwissPairing;
+use self::tournament::Tiebreaker;
+use self::simulation::Simulation;
+use self::swiss::SwissStatus;
+use self::storage::StorageBackend;
+use self::history::History;
+
+pub mod clinch;
+pub mod export;
+pub mod form;
+mod history;
 mod model_backwards_compat;
+mod presets;
+mod schedule;
+pub mod simulation;
+pub mod standings;
+pub mod storage;
+pub mod swiss;
 pub mod tournament;
+mod what_if;
 
 // {"tournaments":{"0":{"id":0,"name":"LCS","stages":{"1":{"id":1,"tournament_id":0,"name":"Group Stage","teams":{"2":{"id":2,"name":"FNC"},"75":{"id":75,"name":"TH"},"76":{"id":76,"name":"KOI"},"77":{"id":77,"name":"XL"},"78":{"id":78,"name":"SK"},"79":{"id":79,"name":"VIT"},"80":{"id":80,"name":"BDS"},"91":{"id":91,"name":"MAD"},"92":{"id":92,"name":"G2"},"93":{"id":93,"name":"AST"}},"matches":{"81":{"id":81,"teams":[2,75],"winner":2,"loser":75},"82":{"id":82,"teams":[2,76],"winner":2,"loser":76},"84":{"id":84,"teams":[2,77],"winner":77,"loser":2},"86":{"id":86,"teams":[2,78],"winner":78,"loser":2},"88":{"id":88,"teams":[2,79],"winner":79,"loser":2},"90":{"id":90,"teams":[2,80],"winner":80,"loser":2},"95":{"id":95,"teams":[77,91],"winner":77,"loser":91},"98":{"id":98,"teams":[77,79],"winner":79,"loser":77},"100":{"id":100,"teams":[77,92],"winner":92,"loser":77},"102":{"id":102,"teams":[77,75],"winner":75,"loser":77},"104":{"id":104,"teams":[77,78],"winner":78,"loser":77},"111":{"id":111,"teams":[75,76],"winner":76,"loser":75},"113":{"id":113,"teams":[75,80],"winner":80,"loser":75},"114":{"id":114,"teams":[75,91],"winner":75,"loser":91},"116":{"id":116,"teams":[75,92],"winner":92,"loser":75},"117":{"id":117,"teams":[91,76],"winner":91,"loser":76},"119":{"id":119,"teams":[91,79],"winner":79,"loser":91},"121":{"id":121,"teams":[91,80],"winner":80,"loser":91},"122":{"id":122,"teams":[91,93],"winner":91,"loser":93},"124":{"id":124,"teams":[80,78],"winner":78,"loser":80},"126":{"id":126,"teams":[80,92],"winner":92,"loser":80},"127":{"id":127,"teams":[80,93],"winner":80,"loser":93},"129":{"id":129,"teams":[78,76],"winner":76,"loser":78},"131":{"id":131,"teams":[78,79],"winner":79,"loser":78},"132":{"id":132,"teams":[78,93],"winner":78,"loser":93},"133":{"id":133,"teams":[92,79],"winner":92,"loser":79},"135":{"id":135,"teams":[92,93],"winner":93,"loser":92},"137":{"id":137,"teams":[92,76],"winner":76,"loser":92},"139":{"id":139,"teams":[76,93],"winner":93,"loser":76},"140":{"id":140,"teams":[93,79],"winner":93,"loser":79},"141":{"id":141,"teams":[93,75],"winner":93,"loser":75}}}}}},"next_id":142}
 // {"tournaments":{"0":{"id":0,"name":"LCS","stages":{"1":{"id":1,"tournament_id":0,"name":"Group Stage","teams":{"2":{"id":2,"name":"FNC"},"75":{"id":75,"name":"TH"},"76":{"id":76,"name":"KOI"},"77":{"id":77,"name":"XL"},"78":{"id":78,"name":"SK"},"79":{"id":79,"name":"VIT"},"80":{"id":80,"name":"BDS"},"91":{"id":91,"name":"MAD"},"92":{"id":92,"name":"G2"},"93":{"id":93,"name":"AST"}},"matches":{"81":{"id":81,"teams":[2,75],"winner":2,"loser":75},"82":{"id":82,"teams":[2,76],"winner":2,"loser":76},"84":{"id":84,"teams":[2,77],"winner":77,"loser":2},"86":{"id":86,"teams":[2,78],"winner":78,"loser":2},"88":{"id":88,"teams":[2,79],"winner":79,"loser":2},"90":{"id":90,"teams":[2,80],"winner":80,"loser":2},"95":{"id":95,"teams":[77,91],"winner":77,"loser":91},"98":{"id":98,"teams":[77,79],"winner":79,"loser":77},"100":{"id":100,"teams":[77,92],"winner":92,"loser":77},"102":{"id":102,"teams":[77,75],"winner":75,"loser":77},"104":{"id":104,"teams":[77,78],"winner":78,"loser":77},"111":{"id":111,"teams":[75,76],"winner":76,"loser":75},"113":{"id":113,"teams":[75,80],"winner":80,"loser":75},"114":{"id":114,"teams":[75,91],"winner":75,"loser":91},"116":{"id":116,"teams":[75,92],"winner":92,"loser":75},"117":{"id":117,"teams":[91,76],"winner":91,"loser":76},"119":{"id":119,"teams":[91,79],"winner":79,"loser":91},"121":{"id":121,"teams":[91,80],"winner":80,"loser":91},"122":{"id":122,"teams":[91,93],"winner":91,"loser":93},"124":{"id":124,"teams":[80,78],"winner":78,"loser":80},"126":{"id":126,"teams":[80,92],"winner":92,"loser":80},"127":{"id":127,"teams":[80,93],"winner":80,"loser":93},"129":{"id":129,"teams":[78,76],"winner":76,"loser":78},"131":{"id":131,"teams":[78,79],"winner":79,"loser":78},"132":{"id":132,"teams":[78,93],"winner":78,"loser":93},"133":{"id":133,"teams":[92,79],"winner":92,"loser":79},"135":{"id":135,"teams":[92,93],"winner":93,"loser":92},"137":{"id":137,"teams":[92,76],"winner":76,"loser":92},"139":{"id":139,"teams":[76,93],"winner":93,"loser":76},"140":{"id":140,"teams":[93,79],"winner":93,"loser":79},"141":{"id":141,"teams":[93,75],"winner":93,"loser":75},"142":{"id":142,"teams":[76,77],"winner":76,"loser":77},"144":{"id":144,"teams":[80,79],"winner":80,"loser":79},"145":{"id":145,"teams":[2,91],"winner":2,"loser":91},"146":{"id":146,"teams":[92,78],"winner":92,"loser":78},"147":{"id":147,"teams":[93,77],"winner":93,"loser":77},"148":{"id":148,"teams":[91,78],"winner":91,"loser":78},"149":{"id":149,"teams":[80,76],"winner":80,"loser":76},"150":{"id":150,"teams":[79,75],"winner":79,"loser":75},"151":{"id":151,"teams":[2,92],"winner":2,"loser":92},"152":{"id":152,"teams":[80,77],"winner":80,"loser":77},"153":{"id":153,"teams":[93,2],"winner":93,"loser":2},"154":{"id":154,"teams":[75,78],"winner":75,"loser":78}}}}},"143":{"id":143,"name":"test","stages":{}}},"next_id":155}
@@ -37,29 +60,94 @@ pub struct Model {
     next_id: usize,
     #[serde(skip)]
     changed_tournaments: Vec<TournamentId>,
+    /// Where the model is saved to. If this is None then changes are not persisted anywhere.
+    #[serde(skip)]
+    storage: Option<Box<dyn StorageBackend>>,
+    /// While in what-if mode, the real data from before it was entered. Any changes made in what-if mode are
+    /// only hypothetical, so this is what gets saved. None when not in what-if mode. See what_if.rs.
+    #[serde(skip)]
+    real_tournaments: Option<IndexMap<TournamentId, Tournament>>,
+    /// Whether the data is locked against editing, so that it can be viewed without accidentally changing anything.
+    /// This is a setting rather than part of the data, so is saved separately. See set_locked.
+    #[serde(skip)]
+    locked: bool,
+    /// The changes which can be undone or redone. See history.rs.
+    #[serde(skip)]
+    history: History,
+    /// The simulations of the remaining matches of round robin stages, which are kept until their tournament changes
+    /// as they can be slow to work out. See get_simulation.
+    #[serde(skip)]
+    simulations: RefCell<HashMap<(TournamentId, StageId), Rc<Simulation>>>,
+}
+
+/// Serializes the same as the Model, but borrows the tournaments so that the real ones can be saved while in what-if mode.
+#[derive(Serialize)]
+struct RealData<'a> {
+    tournaments: &'a IndexMap<TournamentId, Tournament>,
+    next_id: usize,
+}
+
+/// Something that wants to be told about changes to the Model, e.g. the UI.
+pub trait ModelObserver {
+    fn tournament_changed(&mut self, model: &Model, tournament_id: TournamentId);
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Model {
     // The version is stored separately, so that we don't need to deserialize the model in order to check the version.
     // Beware if this key name is changed, we won't be able to load old data!!
-    const LOCAL_STORAGE_VERSION_KEY: &str = "tournament-tracker-version";
-    const VERSION: i32 = 3;
-    const LOCAL_STORAGE_MODEL_KEY: &str = "tournament-tracker-model";
+    const STORAGE_VERSION_KEY: &str = "tournament-tracker-version";
+    const VERSION: i32 = 4;
+    const STORAGE_MODEL_KEY: &str = "tournament-tracker-model";
+    const STORAGE_LOCKED_KEY: &str = "tournament-tracker-locked";
 
     pub fn new() -> Model {
-        Model { tournaments: indexmap!{}, next_id: 0, changed_tournaments: vec![] }
+        Model { tournaments: indexmap!{}, next_id: 0, changed_tournaments: vec![], storage: None, real_tournaments: None, locked: false, history: History::default(), simulations: RefCell::default() }
     }
 
-    pub fn load() -> Model {
+    /// Creates an empty model which will be saved to the given storage.
+    pub fn with_storage(storage: Box<dyn StorageBackend>) -> Model {
+        let mut result = Model::new();
+        result.storage = Some(storage);
+        result
+    }
+
+    /// Loads the model from the given storage, upgrading from older versions if necessary.
+    /// Any further changes will be saved back to the same storage.
+    pub fn load(storage: Box<dyn StorageBackend>) -> Result<Model, String> {
         debug!("Loading!");
 
-        let storage = window().expect("Missing window").local_storage().expect("Error getting localStorage").expect("Missing localStorage");
+        Self::load_any_version(storage.as_ref()).map(|mut m| {
+            m.locked = matches!(storage.get_item(Model::STORAGE_LOCKED_KEY), Ok(Some(x)) if x == "true");
+            m.storage = Some(storage);
+            m.reset_history();
+            m
+        })
+    }
 
+    /// Loads a model from the given storage, upgrading from older versions if necessary.
+    /// The result is not linked to the storage.
+    fn load_any_version(storage: &dyn StorageBackend) -> Result<Model, String> {
         // Check the version, as we may need to deserialize differently for backwards compatibility
-        let res = match storage.get_item(Model::LOCAL_STORAGE_VERSION_KEY) {
+        let version = storage.get_item(Model::STORAGE_VERSION_KEY);
+        // Data from a newer version of the app can't be understood, and loading it as the oldest version would give a confusing error
+        if let Ok(Some(x)) = &version {
+            if x.parse::<i32>().is_ok_and(|v| v > Model::VERSION) {
+                return Err(format!("Unsupported version {x} (the newest that can be loaded is {})", Model::VERSION));
+            }
+        }
+        match version {
             Ok(Some(x)) if x == Model::VERSION.to_string() => {
                 Self::load_current_version(storage)
             },
+            Ok(Some(x)) if &x == "3" => {
+                model_backwards_compat::v3::load_and_upgrade(storage)
+            },
             Ok(Some(x)) if &x == "2" => {
                 model_backwards_compat::v2::load_and_upgrade(storage)
             },
@@ -67,32 +155,35 @@ impl Model {
                 // Assume is old version before we added versioning
                 model_backwards_compat::v1::load_and_upgrade(storage)
             }
-        };
-        match res {
-            Ok(r) => r,
-            Err(e) => {
-                if window().unwrap().confirm_with_message(&format!("Failed to load saved data: {:?}! If this is expected then click OK and it will be reset. Otherwise check what's going on.",
-                    e)) == Ok(true) {
-                    Model::new()
-                } else {
-                    panic!("No data!");
-                }
-            }
         }
     }
 
-    fn load_current_version(storage: Storage) -> Result<Model, String> {
-        match storage.get_item(Model::LOCAL_STORAGE_MODEL_KEY) {
-            Ok(Some(s)) => {
-                match serde_json::from_str::<Model>(&s) {
-                    Ok(m) => Ok(m),
-                    Err(e) => Err(format!("Failed to deserialize data: {:?}", e)),
-                }
-            }
-            e => Err(format!("Failed to load from local storage: {:?}", e)),
+    fn load_current_version(storage: &dyn StorageBackend) -> Result<Model, String> {
+        match storage.get_item(Model::STORAGE_MODEL_KEY) {
+            Ok(Some(s)) => Self::from_json(&s),
+            e => Err(format!("Failed to load from storage: {:?}", e)),
         }
     }
 
+    /// Deserializes a model that was serialized with to_json(), i.e. the current version.
+    /// The result is not linked to any storage.
+    pub fn from_json(s: &str) -> Result<Model, String> {
+        match serde_json::from_str::<Model>(s) {
+            Ok(m) => Ok(m),
+            Err(e) => Err(format!("Failed to deserialize data: {:?}", e)),
+        }
+    }
+
+    /// Serializes the real data, i.e. without any hypothetical changes made in what-if mode.
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(&self.get_real_data()).map_err(|e| format!("Failed to serialize data: {e}"))
+    }
+
+    /// Gets the data which should be saved, which leaves out any hypothetical changes made in what-if mode.
+    fn get_real_data(&self) -> RealData<'_> {
+        RealData { tournaments: self.real_tournaments.as_ref().unwrap_or(&self.tournaments), next_id: self.next_id }
+    }
+
     pub fn get_next_id(&mut self) -> usize {
         let id = self.next_id;
         self.next_id += 1;
@@ -137,10 +228,13 @@ impl Model {
         }
     }
 
-    pub fn add_stage_round_robin(&mut self, tournament_id: TournamentId, name: String) -> Option<StageId> {
+    pub fn add_stage_round_robin(&mut self, tournament_id: TournamentId, name: String, legs: u32) -> Option<StageId> {
+        if legs == 0 {
+            return None;
+        }
         let id = self.get_next_id();
         if let Some(t) = self.tournaments.get_mut(&tournament_id) {
-            t.stages.insert(id, Stage::new_round_robin(id, t.id, name));
+            t.stages.insert(id, Stage::new_round_robin(id, t.id, name, legs));
             let tid = t.id;
             self.changed_tournaments.push(tid);
             Some(id)
@@ -161,6 +255,21 @@ impl Model {
         }
     }
 
+    pub fn add_stage_swiss(&mut self, tournament_id: TournamentId, name: String, rounds: u32, advance_wins: u32, eliminate_losses: u32) -> Option<StageId> {
+        if rounds == 0 || advance_wins == 0 || eliminate_losses == 0 {
+            return None;
+        }
+        let id = self.get_next_id();
+        if let Some(t) = self.tournaments.get_mut(&tournament_id) {
+            t.stages.insert(id, Stage::new_swiss(id, t.id, name, rounds, advance_wins, eliminate_losses));
+            let tid = t.id;
+            self.changed_tournaments.push(tid);
+            Some(id)
+        } else {
+            None
+        }
+    }
+
     pub fn delete_stage(&mut self, tournament_id: TournamentId, stage_id: StageId) -> Result<(), ()> {
         if self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.shift_remove(&stage_id)).is_some() {
             self.changed_tournaments.push(tournament_id);
@@ -180,6 +289,57 @@ impl Model {
         }
     }
 
+    pub fn set_stage_tiebreakers(&mut self, tournament_id: TournamentId, stage_id: StageId, tiebreakers: Vec<Tiebreaker>) -> Result<(), ()> {
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            s.tiebreakers = tiebreakers;
+            self.changed_tournaments.push(tournament_id);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Fails if the format doesn't make sense, or if any of the stage's existing matches have scores which aren't possible in the new format.
+    pub fn set_stage_series_format(&mut self, tournament_id: TournamentId, stage_id: StageId, series_format: SeriesFormat) -> Result<(), ()> {
+        if !series_format.is_valid() {
+            return Err(());
+        }
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            if s.matches.values().any(|m| !series_format.is_valid_score(m.team_a_score, m.team_b_score)) {
+                return Err(());
+            }
+            s.series_format = series_format;
+            self.changed_tournaments.push(tournament_id);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    pub fn set_stage_scoring_system(&mut self, tournament_id: TournamentId, stage_id: StageId, scoring_system: ScoringSystem) -> Result<(), ()> {
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            s.scoring_system = scoring_system;
+            self.changed_tournaments.push(tournament_id);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Sets how many teams at the top of the standings go through to the next stage. 0 means there is no cut.
+    pub fn set_stage_advancing_teams(&mut self, tournament_id: TournamentId, stage_id: StageId, advancing_teams: u32) -> Result<(), ()> {
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            if advancing_teams as usize > s.teams.len() {
+                return Err(());
+            }
+            s.advancing_teams = advancing_teams;
+            self.changed_tournaments.push(tournament_id);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
     pub fn add_team(&mut self, tournament_id: TournamentId, stage_id: StageId, name: String) -> Option<TeamId> {
         let id = self.get_next_id();
         if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
@@ -195,10 +355,20 @@ impl Model {
         if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
             // Remove any matches this team was in
             s.matches.retain(|_, m| !m.contains(team_id));
+            match &mut s.kind {
+                StageKind::RoundRobin { schedule, .. } => schedule.retain(|m| m.team_a != team_id && m.team_b != team_id),
+                StageKind::Swiss { pairings, .. } => {
+                    for round in pairings {
+                        round.retain(|p| p.team_a != team_id && p.team_b != team_id);
+                    }
+                }
+                StageKind::Bracket { .. } => (),
+            }
 
             if s.teams.shift_remove(&team_id).is_none() {
                 return Err(());
             }
+            s.clear_invalid_fixture_results();
             self.changed_tournaments.push(tournament_id);
             Ok(())
         } else {
@@ -206,6 +376,13 @@ impl Model {
         }
     }
 
+    /// Adds a match which was played to separate tied teams, so doesn't count towards their records.
+    pub fn add_tiebreaker_match(&mut self, tournament_id: TournamentId, stage_id: StageId, team_a: TeamId, team_b: TeamId, team_a_score: u32, team_b_score: u32) -> Option<MatchId> {
+        let id = self.add_match(tournament_id, stage_id, team_a, team_b, team_a_score, team_b_score)?;
+        self.set_match_tiebreaker(tournament_id, stage_id, id, true).ok()?;
+        Some(id)
+    }
+
     pub fn add_match(&mut self, tournament_id: TournamentId, stage_id: StageId, team_a: TeamId, team_b: TeamId, team_a_score: u32, team_b_score: u32) -> Option<MatchId> {
         let id = self.get_next_id();
         if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
@@ -215,20 +392,62 @@ impl Model {
             if team_a == team_b {
                 return None;
             }
+            if !s.series_format.is_valid_score(team_a_score, team_b_score) {
+                return None;
+            }
 
-            s.matches.insert(id, Match { id, team_a, team_b, team_a_score, team_b_score });
+            s.matches.insert(id, Match { id, team_a, team_b, team_a_score, team_b_score, is_tiebreaker: false });
+            s.sync_schedule();
             self.changed_tournaments.push(tournament_id);
             return Some(id)
         }
-        return None;
+        None
+    }
+
+    pub fn set_match_score(&mut self, tournament_id: TournamentId, stage_id: StageId, match_id: MatchId, team_a_score: u32, team_b_score: u32) -> Result<(), ()> {
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            if !s.series_format.is_valid_score(team_a_score, team_b_score) {
+                return Err(());
+            }
+            if let Some(m) = s.matches.get_mut(&match_id) {
+                m.team_a_score = team_a_score;
+                m.team_b_score = team_b_score;
+                // The winner might have changed, so later fixtures in a bracket may now have the wrong teams
+                s.clear_invalid_fixture_results();
+                self.changed_tournaments.push(tournament_id);
+                Ok(())
+            } else {
+                Err(())
+            }
+        } else {
+            Err(())
+        }
+    }
+
+    pub fn set_match_tiebreaker(&mut self, tournament_id: TournamentId, stage_id: StageId, match_id: MatchId, is_tiebreaker: bool) -> Result<(), ()> {
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            if let Some(m) = s.matches.get_mut(&match_id) {
+                m.is_tiebreaker = is_tiebreaker;
+                // Tiebreaker matches don't count as playing scheduled matches
+                s.sync_schedule();
+                self.changed_tournaments.push(tournament_id);
+                Ok(())
+            } else {
+                Err(())
+            }
+        } else {
+            Err(())
+        }
     }
 
     pub fn delete_match(&mut self, tournament_id: TournamentId, stage_id: StageId, match_id: MatchId) -> Result<(), ()> {
         if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
-            //TODO: what about any fixtures that were linked to this match?
             if s.matches.shift_remove(&match_id).is_none() {
                 return Err(());
             }
+            // Any fixture that this was the result of is now unplayed, so later fixtures no longer know their teams
+            s.clear_invalid_fixture_results();
+            s.sync_schedule();
             self.changed_tournaments.push(tournament_id);
             Ok(())
         } else {
@@ -251,16 +470,16 @@ impl Model {
 
     pub fn add_fixture(&mut self, tournament_id: TournamentId, stage_id: StageId, layout: (i32, i32), team_a: FixtureTeam, team_b: FixtureTeam) -> Option<FixtureId> {
         let id = self.get_next_id();
-        if let Some(StageKind::Bracket { fixtures }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).and_then(|s| Some(&mut s.kind)) {
-            fixtures.insert(id, Fixture { id, layout, match_id: None, team_a, team_b });
+        if let Some(StageKind::Bracket { fixtures }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).map(|s| &mut s.kind) {
+            fixtures.insert(id, Fixture { id, layout, match_id: None, team_a, team_b, exits: vec![] });
             self.changed_tournaments.push(tournament_id);
             return Some(id)
         }
-        return None;
+        None
     }
 
     pub fn set_fixture_layout(&mut self, tournament_id: TournamentId, stage_id: StageId, fixture_id: FixtureId, layout: (i32, i32)) -> Result<(), ()> {
-        if let Some(StageKind::Bracket { fixtures }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).and_then(|s| Some(&mut s.kind)) {
+        if let Some(StageKind::Bracket { fixtures }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).map(|s| &mut s.kind) {
             if let Some(f) = fixtures.get_mut(&fixture_id) {
                 f.layout = layout;
                 self.changed_tournaments.push(tournament_id);
@@ -273,14 +492,38 @@ impl Model {
         }
     }
 
-    pub fn set_fixture_input(&mut self, tournament_id: TournamentId, stage_id: StageId, fixture_id: FixtureId, input: FixtureInput, team: FixtureTeam) -> Result<(), ()> {
-        //TODO: if a match already exists for the fixture, does it need updating at all?
-        if let Some(StageKind::Bracket { fixtures }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).and_then(|s| Some(&mut s.kind)) {
+    /// Records that the team with the given outcome from a fixture leaves the bracket there, e.g. advancing to the next stage.
+    /// None means that they don't, or that it isn't known. See Fixture::exits.
+    pub fn set_fixture_exit(&mut self, tournament_id: TournamentId, stage_id: StageId, fixture_id: FixtureId, outcome: Outcome, exit: Option<BracketExit>) -> Result<(), ()> {
+        if let Some(StageKind::Bracket { fixtures }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).map(|s| &mut s.kind) {
             if let Some(f) = fixtures.get_mut(&fixture_id) {
+                f.exits.retain(|(o, _)| *o != outcome);
+                if let Some(exit) = exit {
+                    f.exits.push((outcome, exit));
+                }
+                self.changed_tournaments.push(tournament_id);
+                Ok(())
+            } else {
+                Err(())
+            }
+        } else {
+            Err(())
+        }
+    }
+
+    pub fn set_fixture_input(&mut self, tournament_id: TournamentId, stage_id: StageId, fixture_id: FixtureId, input: FixtureInput, team: FixtureTeam) -> Result<(), ()> {
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            let f = match &mut s.kind {
+                StageKind::Bracket { fixtures } => fixtures.get_mut(&fixture_id),
+                _ => None,
+            };
+            if let Some(f) = f {
                 match input {
                     FixtureInput::TeamA => f.team_a = team,
                     FixtureInput::TeamB => f.team_b = team,
                 }
+                // If the fixture has already been played by different teams, that result (and anything depending on it) is no longer valid
+                s.clear_invalid_fixture_results();
                 self.changed_tournaments.push(tournament_id);
                 Ok(())
             } else {
@@ -291,10 +534,61 @@ impl Model {
         }
     }
 
+    /// Records the result of a fixture, creating the match for it if it hasn't been played yet.
+    /// Both of the fixture's teams need to be known, and the result can't be a draw as someone needs to go through.
+    pub fn set_fixture_score(&mut self, tournament_id: TournamentId, stage_id: StageId, fixture_id: FixtureId, team_a_score: u32, team_b_score: u32) -> Result<(), ()> {
+        if team_a_score == team_b_score {
+            return Err(());
+        }
+        let (team_a, team_b, match_id) = match self.get_stage(tournament_id, stage_id) {
+            Some(s @ Stage { kind: StageKind::Bracket { fixtures }, .. }) => match fixtures.get(&fixture_id) {
+                Some(f) => match (s.get_fixture_team(&f.team_a), s.get_fixture_team(&f.team_b)) {
+                    (Some(a), Some(b)) => (a, b, f.match_id),
+                    _ => return Err(()),
+                }
+                None => return Err(()),
+            }
+            _ => return Err(()),
+        };
+
+        let match_id = self.set_or_add_linked_match_score(tournament_id, stage_id, match_id, (team_a, team_b), (team_a_score, team_b_score))?;
+        if let Some(StageKind::Bracket { fixtures }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).map(|s| &mut s.kind) {
+            if let Some(f) = fixtures.get_mut(&fixture_id) {
+                f.match_id = Some(match_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the score of the match that something (e.g. a fixture) is linked to, or adds a new match if it isn't linked to one yet.
+    /// The scores are in the same order as the teams, which might be the other way round to the existing match.
+    /// Returns the id of the match, for the caller to link to.
+    fn set_or_add_linked_match_score(&mut self, tournament_id: TournamentId, stage_id: StageId, existing_match_id: Option<MatchId>,
+        (team_a, team_b): (TeamId, TeamId), (team_a_score, team_b_score): (u32, u32)) -> Result<MatchId, ()> {
+        let existing_match = existing_match_id.and_then(|m| self.get_stage(tournament_id, stage_id).and_then(|s| s.matches.get(&m)));
+        match existing_match.map(|m| (m.id, m.team_a == team_a)) {
+            Some((m, true)) => self.set_match_score(tournament_id, stage_id, m, team_a_score, team_b_score).map(|_| m),
+            Some((m, false)) => self.set_match_score(tournament_id, stage_id, m, team_b_score, team_a_score).map(|_| m),
+            None => self.add_match(tournament_id, stage_id, team_a, team_b, team_a_score, team_b_score).ok_or(()),
+        }
+    }
+
+    /// Deletes a fixture from a bracket. This fails if any other fixtures take their teams from it, as they would be left
+    /// linked to a fixture that doesn't exist, so those need changing first.
     pub fn delete_fixture(&mut self, tournament_id: TournamentId, stage_id: StageId, fixture_id: FixtureId) -> Result<(), ()> {
-        if let Some(StageKind::Bracket { fixtures }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).and_then(|s| Some(&mut s.kind)) {
-            //TODO: what about other fixtures that were linked to this one?
-            if fixtures.shift_remove(&fixture_id).is_none() {
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            let removed = match &mut s.kind {
+                StageKind::Bracket { fixtures } => {
+                    let is_linked = fixtures.values().any(|f| [f.team_a, f.team_b].iter()
+                        .any(|t| matches!(t, FixtureTeam::Linked { fixture_id: x, .. } if *x == fixture_id)));
+                    if is_linked {
+                        return Err(());
+                    }
+                    fixtures.shift_remove(&fixture_id)
+                }
+                _ => None,
+            };
+            if removed.is_none() {
                 return Err(());
             }
             self.changed_tournaments.push(tournament_id);
@@ -304,30 +598,287 @@ impl Model {
         }
     }
 
+    /// Pairs up the teams which are still playing in a Swiss stage, for the next round.
+    /// Byes aren't supported, so this fails if there's an odd number of teams still playing, as well as if all the rounds
+    /// have been played or the previous round isn't finished yet. The error explains why, so can be shown to the user.
+    pub fn generate_swiss_round(&mut self, tournament_id: TournamentId, stage_id: StageId) -> Result<(), String> {
+        let new_pairings = match self.get_stage(tournament_id, stage_id) {
+            Some(s @ Stage { kind: StageKind::Swiss { rounds, pairings, .. }, .. }) => {
+                if pairings.len() >= *rounds as usize {
+                    return Err(format!("All {rounds} rounds have already been played."));
+                }
+                if pairings.last().is_some_and(|r| r.iter().any(|p| p.match_id.is_none())) {
+                    return Err("All the results of the previous round need entering first.".to_string());
+                }
+                let num_playing = s.teams.keys().filter(|t| s.get_swiss_status(**t) == SwissStatus::Playing).count();
+                if num_playing == 0 {
+                    return Err("Every team has already advanced or been eliminated.".to_string());
+                }
+                if !num_playing.is_multiple_of(2) {
+                    return Err(if pairings.is_empty() {
+                        format!("There are {num_playing} teams, but byes aren't supported so there needs to be an even number. Add or remove a team.")
+                    } else {
+                        format!("There are {num_playing} teams still playing, which can't all be paired up as byes aren't supported.")
+                    });
+                }
+                s.get_next_swiss_pairings().ok_or("Couldn't pair up the teams.")?
+            }
+            _ => return Err("Not a Swiss stage.".to_string()),
+        };
+
+        if let Some(StageKind::Swiss { pairings, .. }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).map(|s| &mut s.kind) {
+            pairings.push(new_pairings.into_iter().map(|(team_a, team_b)| SwissPairing { team_a, team_b, match_id: None }).collect());
+            self.changed_tournaments.push(tournament_id);
+            Ok(())
+        } else {
+            Err("Not a Swiss stage.".to_string())
+        }
+    }
+
+    /// Records the result of a pairing in a Swiss stage, creating the match for it if it hasn't been played yet.
+    pub fn set_swiss_pairing_score(&mut self, tournament_id: TournamentId, stage_id: StageId, round: usize, pairing: usize, team_a_score: u32, team_b_score: u32) -> Result<(), ()> {
+        let (team_a, team_b, match_id) = match self.get_stage(tournament_id, stage_id) {
+            Some(Stage { kind: StageKind::Swiss { pairings, .. }, .. }) => match pairings.get(round).and_then(|r| r.get(pairing)) {
+                Some(p) => (p.team_a, p.team_b, p.match_id),
+                None => return Err(()),
+            }
+            _ => return Err(()),
+        };
+
+        let match_id = self.set_or_add_linked_match_score(tournament_id, stage_id, match_id, (team_a, team_b), (team_a_score, team_b_score))?;
+        if let Some(StageKind::Swiss { pairings, .. }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).map(|s| &mut s.kind) {
+            pairings[round][pairing].match_id = Some(match_id);
+        }
+        Ok(())
+    }
+
+    /// Adds a match to a round robin's schedule, to be played in the given round. The schedule is kept in order of round.
+    /// If these teams have already played a match which isn't part of the schedule, that counts as playing this.
+    pub fn add_scheduled_match(&mut self, tournament_id: TournamentId, stage_id: StageId, team_a: TeamId, team_b: TeamId, round: u32) -> Option<usize> {
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            if !s.teams.contains_key(&team_a) || !s.teams.contains_key(&team_b) || team_a == team_b {
+                return None;
+            }
+            let index = match &mut s.kind {
+                StageKind::RoundRobin { schedule, .. } => {
+                    let index = schedule.iter().take_while(|m| m.round <= round).count();
+                    schedule.insert(index, ScheduledMatch { team_a, team_b, round, match_id: None, time: None });
+                    index
+                }
+                _ => return None,
+            };
+            s.sync_schedule();
+            self.changed_tournaments.push(tournament_id);
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Removes a match from a round robin's schedule. If it has already been played, the match itself is kept, and counts
+    /// as playing any other unplayed scheduled match between the same teams.
+    pub fn delete_scheduled_match(&mut self, tournament_id: TournamentId, stage_id: StageId, index: usize) -> Result<(), ()> {
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            match &mut s.kind {
+                StageKind::RoundRobin { schedule, .. } if index < schedule.len() => {
+                    schedule.remove(index);
+                }
+                _ => return Err(()),
+            }
+            s.sync_schedule();
+            self.changed_tournaments.push(tournament_id);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    pub fn set_scheduled_match_time(&mut self, tournament_id: TournamentId, stage_id: StageId, index: usize, time: Option<String>) -> Result<(), ()> {
+        if let Some(StageKind::RoundRobin { schedule, .. }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).map(|s| &mut s.kind) {
+            match schedule.get_mut(index) {
+                Some(m) => m.time = time,
+                None => return Err(()),
+            }
+            self.changed_tournaments.push(tournament_id);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Names a round of a round robin's schedule, e.g. "Week 1". None goes back to just numbering the round.
+    pub fn set_schedule_round_label(&mut self, tournament_id: TournamentId, stage_id: StageId, round: u32, label: Option<String>) -> Result<(), ()> {
+        if let Some(StageKind::RoundRobin { round_labels, .. }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).map(|s| &mut s.kind) {
+            match label {
+                Some(l) => round_labels.insert(round, l),
+                None => round_labels.shift_remove(&round),
+            };
+            self.changed_tournaments.push(tournament_id);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Records the result of a match in a round robin's schedule, creating the match for it if it hasn't been played yet.
+    pub fn set_scheduled_match_score(&mut self, tournament_id: TournamentId, stage_id: StageId, index: usize, team_a_score: u32, team_b_score: u32) -> Result<(), ()> {
+        let (team_a, team_b, match_id) = match self.get_stage(tournament_id, stage_id) {
+            Some(Stage { kind: StageKind::RoundRobin { schedule, .. }, .. }) => match schedule.get(index) {
+                Some(m) => (m.team_a, m.team_b, m.match_id),
+                None => return Err(()),
+            }
+            _ => return Err(()),
+        };
+
+        let match_id = self.set_or_add_linked_match_score(tournament_id, stage_id, match_id, (team_a, team_b), (team_a_score, team_b_score))?;
+        // Adding a match links it to the first unplayed match between these teams, which might be in an earlier leg
+        if let Some(StageKind::RoundRobin { schedule, .. }) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)).map(|s| &mut s.kind) {
+            for m in schedule.iter_mut().filter(|m| m.match_id == Some(match_id)) {
+                m.match_id = None;
+            }
+            schedule[index].match_id = Some(match_id);
+        }
+        Ok(())
+    }
+
     /// We can't easily notify subscribers about changes to the model during the change itself,
     /// as that would require passing round lots of mutable references which Rust doesn't like.
     /// Instead we batch them up and handle them all "at the end".
-    pub fn process_updates(&mut self, ui: &mut Ui) {
-        for t in &self.changed_tournaments {
-           ui.tournament_changed(self, *t);
+    pub fn process_updates(&mut self, observer: &mut dyn ModelObserver) {
+        // Do this first, so that observers can see whether there's anything to undo
+        self.record_history();
+        let changed_tournaments = std::mem::take(&mut self.changed_tournaments);
+        // Anything worked out from the old data is out of date, so needs to be before the observers look at it
+        self.simulations.borrow_mut().retain(|(t, _), _| !changed_tournaments.contains(t));
+        for t in &changed_tournaments {
+           observer.tournament_changed(self, *t);
         }
-        if !self.changed_tournaments.is_empty() {
+        if !changed_tournaments.is_empty() {
             self.save();
         }
-        self.changed_tournaments.clear();
     }
 
-    pub fn save(&self) {
+    pub fn save(&mut self) {
         debug!("Saving!");
 
-        let storage = window().expect("Missing window").local_storage().expect("Error getting localStorage").expect("Missing localStorage");
+        let s = match self.to_json() {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Error saving: {e}");
+                return;
+            }
+        };
+
+        if let Some(storage) = self.storage.as_mut() {
+            // Store version
+            storage.set_item(Model::STORAGE_VERSION_KEY, &Model::VERSION.to_string()).expect("Failed to save");
+            storage.set_item(Model::STORAGE_MODEL_KEY, &s).expect("Failed to save");
+        }
+    }
 
-        // Store version
-        storage.set_item(Model::LOCAL_STORAGE_VERSION_KEY, &Model::VERSION.to_string()).expect("Failed to save");
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
 
-        match serde_json::to_string(&self) {
-            Ok(s) => storage.set_item(Model::LOCAL_STORAGE_MODEL_KEY, &s).expect("Failed to save"),
-            Err(e) => error!("Error saving: {e}"),
-        };
+    /// Locks or unlocks the data against editing. It's up to the UI to not make any changes while locked - the Model
+    /// just remembers the setting (saving it straight away, so that it's kept after reloading).
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+        if let Some(storage) = self.storage.as_mut() {
+            if let Err(e) = storage.set_item(Model::STORAGE_LOCKED_KEY, &locked.to_string()) {
+                error!("Failed to save lock setting: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::model::storage::{InMemoryStorageBackend, StorageBackend};
+    use crate::model::tournament::{FixtureTeam, Outcome, StageKind, Tiebreaker};
+
+    /// Puts some serialized data into storage, as if it was saved by the given version (None for v1, which didn't store one).
+    fn storage_with(version: Option<&str>, model_json: &str) -> Box<dyn StorageBackend> {
+        let mut storage = InMemoryStorageBackend::new();
+        if let Some(v) = version {
+            storage.set_item("tournament-tracker-version", v).unwrap();
+        }
+        storage.set_item("tournament-tracker-model", model_json).unwrap();
+        Box::new(storage)
+    }
+
+    #[test]
+    fn save_then_load() {
+        let mut model = Model::with_storage(Box::new(InMemoryStorageBackend::new()));
+        let t = model.add_tournament("LEC".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 2).unwrap();
+        let a = model.add_team(t, s, "FNC".to_string()).unwrap();
+        let b = model.add_team(t, s, "G2".to_string()).unwrap();
+        let m = model.add_match(t, s, a, b, 1, 0).unwrap();
+        model.save();
+
+        let storage = model.storage.take().unwrap();
+        assert_eq!(storage.get_item("tournament-tracker-version"), Ok(Some(Model::VERSION.to_string())));
+        let loaded = Model::load(storage).unwrap();
+        let stage = loaded.get_stage(t, s).unwrap();
+        assert_eq!(loaded.get_tournaments()[&t].name, "LEC");
+        assert!(matches!(stage.kind, StageKind::RoundRobin { legs: 2, .. }));
+        assert_eq!(stage.teams[&b].name, "G2");
+        assert_eq!(stage.matches[&m], model.get_stage(t, s).unwrap().matches[&m]);
+        // New ids mustn't clash with the loaded ones
+        assert_eq!(loaded.next_id, model.next_id);
+    }
+
+    #[test]
+    fn load_v1() {
+        let storage = storage_with(None, r#"{"tournaments":{"0":{"id":0,"name":"LCS","stages":{"1":{"id":1,"tournament_id":0,"name":"Groups",
+            "teams":{"2":{"id":2,"name":"FNC"},"3":{"id":3,"name":"G2"}},"matches":{"4":{"id":4,"teams":[2,3],"winner":3,"loser":2}}}}}},"next_id":5}"#);
+        let model = Model::load(storage).unwrap();
+        let stage = model.get_stage(0, 1).unwrap();
+        assert!(matches!(stage.kind, StageKind::RoundRobin { legs: 1, .. }));
+        assert_eq!(stage.teams.len(), 2);
+        let m = &stage.matches[&4];
+        assert_eq!((m.team_a, m.team_b, m.team_a_score, m.team_b_score), (2, 3, 0, 1));
+        assert_eq!(m.get_winner(), Some(3));
+        assert_eq!(model.next_id, 5);
+    }
+
+    #[test]
+    fn load_v2() {
+        let storage = storage_with(Some("2"), r#"{"tournaments":{"0":{"id":0,"name":"Worlds","stages":{"1":{"id":1,"tournament_id":0,"name":"Playoffs",
+            "teams":{"2":{"id":2,"name":"T1"},"3":{"id":3,"name":"GEN"}},"matches":{"4":{"id":4,"team_a":2,"team_b":3,"team_a_score":3,"team_b_score":1}},
+            "kind":{"Bracket":{"fixtures":{"5":{"id":5,"layout":[0,0],"match_id":4,"team_a":{"Fixed":2},"team_b":{"Fixed":3}},
+                "6":{"id":6,"layout":[200,0],"match_id":null,"team_a":{"Winner":5},"team_b":{"Loser":5}}}}}}}}},"next_id":7}"#);
+        let model = Model::load(storage).unwrap();
+        let stage = model.get_stage(0, 1).unwrap();
+        match &stage.kind {
+            StageKind::Bracket { fixtures } => {
+                assert_eq!(fixtures[&5].match_id, Some(4));
+                assert!(matches!(fixtures[&6].team_a, FixtureTeam::Linked { fixture_id: 5, outcome: Outcome::Winner }));
+                assert!(matches!(fixtures[&6].team_b, FixtureTeam::Linked { fixture_id: 5, outcome: Outcome::Loser }));
+            }
+            _ => panic!("Expected a bracket"),
+        }
+        let m = &stage.matches[&4];
+        assert_eq!((m.team_a_score, m.team_b_score, m.is_tiebreaker), (3, 1, false));
+    }
+
+    #[test]
+    fn load_v3() {
+        let storage = storage_with(Some("3"), r#"{"tournaments":{"0":{"id":0,"name":"LPL","stages":{"1":{"id":1,"tournament_id":0,"name":"Split",
+            "teams":{"2":{"id":2,"name":"JDG"},"3":{"id":3,"name":"BLG"}},"matches":{"4":{"id":4,"team_a":2,"team_b":3,"team_a_score":0,"team_b_score":2}},
+            "kind":{"RoundRobin":{"legs":2}},"tiebreakers":["ScoreDifferential","HeadToHead"]}}}},"next_id":5}"#);
+        let model = Model::load(storage).unwrap();
+        let stage = model.get_stage(0, 1).unwrap();
+        assert!(matches!(stage.kind, StageKind::RoundRobin { legs: 2, .. }));
+        assert_eq!(stage.tiebreakers, vec![Tiebreaker::ScoreDifferential, Tiebreaker::HeadToHead]);
+        let m = &stage.matches[&4];
+        assert_eq!((m.team_a_score, m.team_b_score, m.is_tiebreaker), (0, 2, false));
+    }
+    #[test]
+    fn load_future_version() {
+        let storage = storage_with(Some("99"), r#"{"something":"new"}"#);
+        assert_eq!(Model::load(storage).err(), Some(format!("Unsupported version 99 (the newest that can be loaded is {})", Model::VERSION)));
     }
 }
@@ -0,0 +1,223 @@
+use crate::model::standings::{Placement, to_ordinal};
+use crate::model::tournament::{Stage, StageKind, TeamId};
+
+/// What a team in a round robin is already guaranteed, or has already missed out on, whatever happens in the remaining matches.
+/// See Stage::get_clinch_statuses.
+#[derive(Debug)]
+pub struct ClinchStatus {
+    pub team_id: TeamId,
+    /// How many places at the top of the standings the team is competing for: the stage's advancing teams, or 1 if that isn't set.
+    pub places: usize,
+    pub clinched_first: bool,
+    /// Whether the team is certain to finish in the top `places`.
+    pub clinched_places: bool,
+    /// Whether the team can no longer finish in the top `places`.
+    pub eliminated: bool,
+    /// How many more wins would make the team certain to finish in the top `places`, whatever the other results are.
+    /// None if they have already clinched or been eliminated, or if winning every remaining match wouldn't be enough on its own.
+    pub wins_to_clinch: Option<usize>,
+}
+
+impl ClinchStatus {
+    /// A short description of what has been decided for this team, e.g. "Clinched top 4". None if nothing has been decided yet.
+    pub fn get_badge(&self) -> Option<String> {
+        if self.clinched_first {
+            Some("Clinched 1st".to_string())
+        } else if self.clinched_places {
+            Some(format!("Clinched top {}", self.places))
+        } else if self.eliminated {
+            Some("Eliminated".to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Explains what the team needs to do to clinch, e.g. "FNC clinches top 4 with 1 more win".
+    pub fn get_explanation(&self, team_name: &str) -> Option<String> {
+        let target = if self.places == 1 { to_ordinal(1) } else { format!("top {}", self.places) };
+        self.wins_to_clinch.map(|w| format!("{team_name} clinches {target} with {w} more {}", if w == 1 { "win" } else { "wins" }))
+    }
+}
+
+/// Keeps track of where each team finishes for every possible set of results of the remaining matches, so that the clinch
+/// statuses can be worked out exactly. This is fed each set of results as the remaining matches are played out,
+/// which is done alongside working out each team's chances (see Stage::simulate_remaining_matches).
+pub(crate) struct ClinchTracker {
+    /// The teams, in standings order.
+    team_ids: Vec<TeamId>,
+    places: usize,
+    /// Matches after these are the simulated ones.
+    num_real_matches: usize,
+    always_first: Vec<bool>,
+    could_make_places: Vec<bool>,
+    /// The most remaining matches that each team can win and still miss out. None if they never miss out.
+    most_wins_missing_out: Vec<Option<usize>>,
+}
+
+impl ClinchTracker {
+    pub(crate) fn new(stage: &Stage, team_ids: &[TeamId]) -> ClinchTracker {
+        ClinchTracker { team_ids: team_ids.to_vec(), places: stage.get_clinch_places(), num_real_matches: stage.matches.len(),
+            always_first: vec![true; team_ids.len()], could_make_places: vec![false; team_ids.len()], most_wins_missing_out: vec![None; team_ids.len()] }
+    }
+
+    /// Takes into account one possible set of results, given as a copy of the stage with the simulated matches added after the real ones,
+    /// along with its placements (which the caller has already worked out).
+    pub(crate) fn add_outcome(&mut self, outcome: &Stage, placements: &[Placement]) {
+        for placement in placements {
+            // Teams which are tied could end up in any of the positions they share
+            let lowest_position = placement.position + placement.team_ids.len() - 1;
+            for t in &placement.team_ids {
+                if let Some(i) = self.team_ids.iter().position(|x| x == t) {
+                    self.always_first[i] &= lowest_position == 1;
+                    self.could_make_places[i] |= placement.position <= self.places;
+                    if lowest_position > self.places {
+                        let wins = outcome.matches.values().skip(self.num_real_matches).filter(|m| m.get_winner() == Some(*t)).count();
+                        self.most_wins_missing_out[i] = std::cmp::max(self.most_wins_missing_out[i], Some(wins));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gets the statuses once every possible set of results has been added. The entries are in standings order.
+    pub(crate) fn get_statuses(&self, remaining_matches: &[(TeamId, TeamId)]) -> Vec<ClinchStatus> {
+        let num_remaining = |t: TeamId| remaining_matches.iter().filter(|(a, b)| *a == t || *b == t).count();
+        self.team_ids.iter().enumerate().map(|(i, &team_id)| {
+            let clinched_places = self.most_wins_missing_out[i].is_none();
+            let eliminated = !self.could_make_places[i];
+            ClinchStatus { team_id, places: self.places, clinched_first: self.always_first[i], clinched_places, eliminated,
+                wins_to_clinch: self.most_wins_missing_out[i].map(|w| w + 1).filter(|w| !eliminated && *w <= num_remaining(team_id)) }
+        }).collect()
+    }
+}
+
+impl Stage {
+    /// Works out which teams in a round robin have clinched 1st place or a place in the top `advancing_teams`, or been eliminated
+    /// from them, based on the matches still to be played. If there are few enough possible sets of results then every one is
+    /// tried, taking the tiebreakers into account, so these are exact. Otherwise only the points that each team could still get are
+    /// considered, so a team is only counted as having clinched if nobody else can catch them (even on points), and as eliminated
+    /// if enough teams are certain to finish ahead of them on points.
+    /// Teams which could end up tied across the cut haven't clinched, as it might come down to a tiebreaker match.
+    /// The entries are in standings order. Empty if this isn't a round robin.
+    /// This plays out the remaining matches, so if the chances are needed too then use simulate_remaining_matches instead,
+    /// which works both out at once.
+    pub fn get_clinch_statuses(&self) -> Vec<ClinchStatus> {
+        self.simulate_remaining_matches().map(|s| s.clinch_statuses).unwrap_or_default()
+    }
+
+    /// How many places at the top of the standings the teams are competing for. See ClinchStatus::places.
+    fn get_clinch_places(&self) -> usize {
+        match std::cmp::min(self.advancing_teams as usize, self.teams.len()) {
+            0 => 1,
+            n => n,
+        }
+    }
+
+    /// Works out the clinch statuses from only the points that each team could still get, for when there are too many possible
+    /// sets of results to try them all. See get_clinch_statuses.
+    pub(crate) fn get_clinch_statuses_by_points(&self, remaining_matches: &[(TeamId, TeamId)]) -> Vec<ClinchStatus> {
+        if !matches!(self.kind, StageKind::RoundRobin { .. }) {
+            return vec![];
+        }
+
+        let places = self.get_clinch_places();
+        let team_ids: Vec<TeamId> = self.get_standings().iter().map(|e| e.team_id).collect();
+        let num_remaining = |t: TeamId| remaining_matches.iter().filter(|(a, b)| *a == t || *b == t).count();
+
+        // The most and fewest points that a team can get from one match, and the fewest they can get from winning it
+        let scores = self.series_format.get_possible_scores();
+        let points = |team_score, opponent_score| self.scoring_system.get_score_points(team_score, opponent_score);
+        let max_match_points = scores.iter().map(|&(a, b)| points(a, b)).max().unwrap_or(0);
+        let min_match_points = scores.iter().map(|&(a, b)| points(a, b)).min().unwrap_or(0);
+        let min_win_points = scores.iter().filter(|(a, b)| a > b).map(|&(a, b)| points(a, b)).min().unwrap_or(max_match_points);
+
+        let max_points = |t: TeamId| self.get_points(t) + num_remaining(t) as i64 * max_match_points;
+        let min_points = |t: TeamId| self.get_points(t) + num_remaining(t) as i64 * min_match_points;
+        // Only teams with strictly more points are certain to finish above or below, as otherwise it depends on the tiebreakers
+        let num_could_catch = |t: TeamId, points: i64| team_ids.iter().filter(|&&x| x != t && max_points(x) >= points).count();
+        let num_certain_above = |t: TeamId| team_ids.iter().filter(|&&x| x != t && min_points(x) > max_points(t)).count();
+
+        team_ids.iter().map(|&team_id| {
+            let clinched_places = num_could_catch(team_id, min_points(team_id)) < places;
+            let eliminated = num_certain_above(team_id) >= places;
+            let n = num_remaining(team_id) as i64;
+            // Assume the team loses every match they don't need to win, and that all their wins are by the smallest margin.
+            // Beating a rival also stops them getting points, but that isn't taken into account, so more wins might be needed than this.
+            let wins_to_clinch = if clinched_places || eliminated { None } else {
+                (1..=n).find(|&w| num_could_catch(team_id, self.get_points(team_id) + w * min_win_points + (n - w) * min_match_points) < places)
+                    .map(|w| w as usize)
+            };
+            ClinchStatus { team_id, places, clinched_first: num_could_catch(team_id, min_points(team_id)) == 0, clinched_places, eliminated, wins_to_clinch }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::model::tournament::{StageId, TeamId, TournamentId};
+
+    /// A single round robin between A, B, C and D, where A has won all their matches, C has lost all theirs,
+    /// and B and D have beaten C, so only B v D is left to play.
+    fn new_round_robin(advancing_teams: u32) -> (Model, TournamentId, StageId, [TeamId; 4]) {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 1).unwrap();
+        let [a, b, c, d] = ["A", "B", "C", "D"].map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        for (winner, loser) in [(a, b), (a, c), (a, d), (b, c), (d, c)] {
+            model.add_match(t, s, winner, loser, 1, 0).unwrap();
+        }
+        model.set_stage_advancing_teams(t, s, advancing_teams).unwrap();
+        (model, t, s, [a, b, c, d])
+    }
+
+    #[test]
+    fn exact() {
+        let (model, t, s, [a, b, c, d]) = new_round_robin(2);
+        let stage = model.get_stage(t, s).unwrap();
+        let statuses = stage.get_clinch_statuses();
+        let status = |team: TeamId| statuses.iter().find(|x| x.team_id == team).unwrap();
+
+        assert!(status(a).clinched_first && status(a).clinched_places);
+        assert_eq!(status(a).get_badge().as_deref(), Some("Clinched 1st"));
+        assert!(status(c).eliminated && !status(c).clinched_places);
+        assert_eq!(status(c).get_badge().as_deref(), Some("Eliminated"));
+        // Whoever wins B v D goes through
+        for team in [b, d] {
+            assert!(!status(team).clinched_places && !status(team).eliminated);
+            assert_eq!(status(team).get_badge(), None);
+            assert_eq!(status(team).wins_to_clinch, Some(1));
+        }
+        assert_eq!(status(b).get_explanation("B").as_deref(), Some("B clinches top 2 with 1 more win"));
+        assert_eq!(status(a).get_explanation("A"), None);
+    }
+
+    #[test]
+    fn exact_for_first_place() {
+        // Without any advancing teams, only 1st place matters, which A has already got
+        let (model, t, s, [a, b, _, d]) = new_round_robin(0);
+        let statuses = model.get_stage(t, s).unwrap().get_clinch_statuses();
+        assert!(statuses.iter().all(|x| x.places == 1));
+        assert!(statuses.iter().all(|x| x.team_id == a || (x.eliminated && x.wins_to_clinch.is_none())));
+        assert_eq!(statuses.iter().find(|x| x.team_id == b).unwrap().get_badge().as_deref(), Some("Eliminated"));
+        assert_eq!(statuses.iter().find(|x| x.team_id == d).unwrap().get_explanation("D"), None);
+    }
+
+    #[test]
+    fn by_points() {
+        let (model, t, s, [a, b, c, d]) = new_round_robin(2);
+        let stage = model.get_stage(t, s).unwrap();
+        let statuses = stage.get_clinch_statuses_by_points(&stage.get_remaining_matches());
+        let status = |team: TeamId| statuses.iter().find(|x| x.team_id == team).unwrap();
+
+        // A has 3 wins and nobody else can get more than 2
+        assert!(status(a).clinched_first && status(a).clinched_places);
+        // A, B and D all have more wins than C can get
+        assert!(status(c).eliminated);
+        for team in [b, d] {
+            assert!(!status(team).clinched_first && !status(team).clinched_places && !status(team).eliminated);
+            // Winning B v D means the other can't catch up, but only points are looked at so that isn't known
+            assert_eq!(status(team).wins_to_clinch, None);
+        }
+    }
+}
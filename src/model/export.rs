@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::model::Model;
+use crate::model::storage::InMemoryStorageBackend;
+use crate::model::storage::StorageBackend;
+use crate::model::tournament::{Tournament, TournamentId};
+
+/// The format of an exported file. This includes the version of the model, so that files exported
+/// from older versions of the app can still be imported (and upgraded).
+#[derive(Serialize, Deserialize, Debug)]
+struct ExportDocument {
+    format: String,
+    version: i32,
+    model: serde_json::Value,
+}
+
+/// What to do with the existing data when importing.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ImportMode {
+    /// The imported tournaments are added alongside the existing ones.
+    Merge,
+    /// All existing tournaments are deleted and replaced with the imported ones.
+    Replace,
+}
+
+impl Model {
+    // Used to check that a file being imported is one of ours.
+    const EXPORT_FORMAT: &str = "tournament-tracker";
+
+    /// Serializes the whole model into a self-describing document, which can be loaded again with import_json().
+    /// Like saving, this leaves out any hypothetical changes made in what-if mode.
+    pub fn export_json(&self) -> Result<String, String> {
+        let model = serde_json::to_value(self.get_real_data()).map_err(|e| format!("Failed to serialize data: {e}"))?;
+        let doc = ExportDocument { format: Model::EXPORT_FORMAT.to_string(), version: Model::VERSION, model };
+        serde_json::to_string_pretty(&doc).map_err(|e| format!("Failed to serialize data: {e}"))
+    }
+
+    /// Serializes a single tournament (including all its stages, teams etc.) so that it can be shared
+    /// with somebody else. This uses the same format as export_json(), so is loaded with import_json().
+    pub fn export_tournament_json(&self, tournament_id: TournamentId) -> Result<String, String> {
+        let tournament = self.get_real_data().tournaments.get(&tournament_id).ok_or(format!("No tournament {tournament_id}"))?;
+        // A model containing just this one tournament
+        let model = serde_json::json!({
+            "tournaments": { tournament_id.to_string(): tournament },
+            "next_id": self.next_id,
+        });
+        let doc = ExportDocument { format: Model::EXPORT_FORMAT.to_string(), version: Model::VERSION, model };
+        serde_json::to_string_pretty(&doc).map_err(|e| format!("Failed to serialize data: {e}"))
+    }
+
+    /// Loads a document produced by export_json() or export_tournament_json() (possibly from an older version) into this model.
+    pub fn import_json(&mut self, s: &str, mode: ImportMode) -> Result<(), String> {
+        if self.is_what_if() {
+            return Err("Can't import while in what-if mode".to_string());
+        }
+        let doc = serde_json::from_str::<ExportDocument>(s).map_err(|e| format!("Failed to deserialize data: {e}"))?;
+        if doc.format != Model::EXPORT_FORMAT {
+            return Err(format!("Unrecognised format '{}'", doc.format));
+        }
+
+        // Go through the same route as loading from storage, so that old versions get upgraded
+        let mut storage = InMemoryStorageBackend::new();
+        storage.set_item(Model::STORAGE_VERSION_KEY, &doc.version.to_string())?;
+        storage.set_item(Model::STORAGE_MODEL_KEY, &doc.model.to_string())?;
+        let imported = Model::load_any_version(&storage)?;
+
+        match mode {
+            ImportMode::Merge => {
+                for (_, t) in imported.tournaments {
+                    self.add_imported_tournament(t);
+                }
+            }
+            ImportMode::Replace => {
+                self.changed_tournaments.extend(self.tournaments.keys());
+                self.changed_tournaments.extend(imported.tournaments.keys());
+                self.tournaments = imported.tournaments;
+                // Don't re-use any IDs from the old data, in case anything is still referring to them
+                self.next_id = std::cmp::max(self.next_id, imported.next_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds a tournament from another model to this one.
+    /// IDs are unique across the whole model, so everything in the tournament is given a new ID.
+    fn add_imported_tournament(&mut self, mut tournament: Tournament) {
+        let mut new_ids = HashMap::<usize, usize>::new();
+        tournament.remap_ids(&mut |old_id| *new_ids.entry(old_id).or_insert_with(|| self.get_next_id()));
+
+        self.changed_tournaments.push(tournament.id);
+        self.tournaments.insert(tournament.id, tournament);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::model::export::ImportMode;
+    use crate::model::tournament::{FixtureTeam, Stage, StageKind, Tournament, TournamentId};
+
+    fn new_model(tournament_name: &str) -> Model {
+        let mut model = Model::new();
+        let t = model.add_tournament(tournament_name.to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 1).unwrap();
+        let [a, b] = ["A", "B"].map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        model.add_match(t, s, a, b, 1, 0).unwrap();
+        model
+    }
+
+    fn get_names(model: &Model) -> Vec<&str> {
+        model.get_tournaments().values().map(|t| &t.name[..]).collect()
+    }
+
+    #[test]
+    fn replace() {
+        let exported = new_model("Exported");
+        let json = exported.export_json().unwrap();
+
+        // The existing data has used more ids than the imported data, which mustn't be reused
+        let mut model = new_model("Existing");
+        for _ in 0..10 {
+            model.get_next_id();
+        }
+        let next_id = model.next_id;
+        model.import_json(&json, ImportMode::Replace).unwrap();
+        assert_eq!(get_names(&model), vec!["Exported"]);
+        assert_eq!(model.next_id, next_id);
+        let (t, tournament) = exported.get_tournaments().first().unwrap();
+        assert_eq!(model.get_tournament(*t).unwrap().stages.len(), tournament.stages.len());
+
+        // Whereas if the imported data has used more, the next id needs to be after them
+        let mut model = Model::new();
+        model.import_json(&json, ImportMode::Replace).unwrap();
+        assert_eq!(get_names(&model), vec!["Exported"]);
+        assert_eq!(model.next_id, exported.next_id);
+        assert_eq!(model.to_json(), exported.to_json());
+    }
+
+    #[test]
+    fn not_in_what_if_mode() {
+        let json = new_model("Exported").export_json().unwrap();
+        let mut model = new_model("Existing");
+        model.enter_what_if().unwrap();
+        assert!(model.import_json(&json, ImportMode::Merge).is_err());
+        assert!(model.import_json(&json, ImportMode::Replace).is_err());
+        assert_eq!(get_names(&model), vec!["Existing"]);
+    }
+
+    #[test]
+    fn old_version() {
+        let json = r#"{"format":"tournament-tracker","version":3,"model":{"tournaments":{"0":{"id":0,"name":"LPL","stages":{"1":{"id":1,"tournament_id":0,
+            "name":"Split","teams":{"2":{"id":2,"name":"JDG"},"3":{"id":3,"name":"BLG"}},"matches":{"4":{"id":4,"team_a":2,"team_b":3,"team_a_score":0,"team_b_score":2}},
+            "kind":{"RoundRobin":{"legs":2}},"tiebreakers":["HeadToHead"]}}}},"next_id":5}}"#;
+        let mut model = Model::new();
+        model.import_json(json, ImportMode::Replace).unwrap();
+        let stage = model.get_stage(0, 1).unwrap();
+        assert!(matches!(stage.kind, StageKind::RoundRobin { legs: 2, .. }));
+        assert_eq!(stage.matches[&4].get_winner(), Some(3));
+        assert_eq!(model.next_id, 5);
+    }
+
+    #[test]
+    fn unsupported_documents() {
+        let mut model = new_model("Existing");
+        let result = model.import_json(r#"{"format":"tournament-tracker","version":99,"model":{"tournaments":{}}}"#, ImportMode::Replace);
+        assert_eq!(result, Err(format!("Unsupported version 99 (the newest that can be loaded is {})", Model::VERSION)));
+        assert_eq!(model.import_json(r#"{"format":"something-else","version":4,"model":{}}"#, ImportMode::Replace),
+            Err("Unrecognised format 'something-else'".to_string()));
+        assert!(model.import_json("not json", ImportMode::Replace).is_err());
+        assert_eq!(get_names(&model), vec!["Existing"]);
+    }
+    /// A tournament with one of each kind of stage, with some results: a generated bracket, a round robin with a schedule,
+    /// and a Swiss stage.
+    fn add_full_tournament(model: &mut Model) -> TournamentId {
+        let t = model.add_tournament("Full".to_string());
+        let names = ["A", "B", "C", "D"];
+
+        let s = model.add_stage_bracket(t, "Playoffs".to_string()).unwrap();
+        let teams = names.map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        model.generate_single_elimination(t, s, &teams, true).unwrap();
+        let fixture_ids: Vec<usize> = match &model.get_stage(t, s).unwrap().kind {
+            StageKind::Bracket { fixtures } => fixtures.keys().copied().collect(),
+            _ => panic!("Not a bracket"),
+        };
+        // The fixtures are added round by round, so this plays the semi-finals before the fixtures linked to them
+        for f in fixture_ids {
+            model.set_fixture_score(t, s, f, 1, 0).unwrap();
+        }
+
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 2).unwrap();
+        names.map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        model.generate_round_robin_schedule(t, s).unwrap();
+        for i in 0..4 {
+            model.set_scheduled_match_score(t, s, i, 1, 0).unwrap();
+        }
+
+        let s = model.add_stage_swiss(t, "Swiss".to_string(), 3, 2, 2).unwrap();
+        names.map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        for round in 0..2 {
+            model.generate_swiss_round(t, s).unwrap();
+            for pairing in 0..2 {
+                model.set_swiss_pairing_score(t, s, round, pairing, 1, 0).unwrap();
+            }
+        }
+        t
+    }
+
+    /// Every id used in a tournament, including its stages, teams, matches and fixtures.
+    fn get_ids(tournament: &Tournament) -> Vec<usize> {
+        let mut result = vec![tournament.id];
+        for stage in tournament.stages.values() {
+            result.push(stage.id);
+            result.extend(stage.teams.keys());
+            result.extend(stage.matches.keys());
+            if let StageKind::Bracket { fixtures } = &stage.kind {
+                result.extend(fixtures.keys());
+            }
+        }
+        result
+    }
+
+    /// Checks that everything in the tournament which refers to something else refers to something in the same stage.
+    fn check_references(tournament: &Tournament) {
+        for stage in tournament.stages.values() {
+            assert_eq!(stage.tournament_id, tournament.id);
+            for m in stage.matches.values() {
+                assert!(stage.teams.contains_key(&m.team_a) && stage.teams.contains_key(&m.team_b));
+            }
+            // Anything with a result needs to be linked to a match between the right teams
+            let check_match = |match_id: Option<usize>, a, b| {
+                if let Some(m) = match_id {
+                    assert!(stage.matches[&m].is_between(a, b));
+                }
+            };
+            match &stage.kind {
+                StageKind::Bracket { fixtures } => {
+                    assert!(fixtures.values().all(|f| f.match_id.is_some()));
+                    for f in fixtures.values() {
+                        for team in [f.team_a, f.team_b] {
+                            match team {
+                                FixtureTeam::Fixed(t) => assert!(stage.teams.contains_key(&t)),
+                                FixtureTeam::Linked { fixture_id, .. } => assert!(fixtures.contains_key(&fixture_id)),
+                            }
+                        }
+                        check_match(f.match_id, stage.get_fixture_team(&f.team_a).unwrap(), stage.get_fixture_team(&f.team_b).unwrap());
+                    }
+                }
+                StageKind::RoundRobin { schedule, .. } => {
+                    assert_eq!(schedule.iter().filter(|m| m.match_id.is_some()).count(), 4);
+                    for m in schedule {
+                        check_match(m.match_id, m.team_a, m.team_b);
+                    }
+                }
+                StageKind::Swiss { pairings, .. } => {
+                    assert_eq!(pairings.iter().flatten().filter(|p| p.match_id.is_some()).count(), 4);
+                    for p in pairings.iter().flatten() {
+                        check_match(p.match_id, p.team_a, p.team_b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn merge() {
+        let mut model = new_model("Existing");
+        let t = add_full_tournament(&mut model);
+        check_references(&model.get_tournaments()[&t]);
+        let json = model.export_tournament_json(t).unwrap();
+
+        // Importing into the model it came from means that every id is already in use
+        let existing_ids: Vec<usize> = model.get_tournaments().values().flat_map(get_ids).collect();
+        model.import_json(&json, ImportMode::Merge).unwrap();
+        assert_eq!(get_names(&model), vec!["Existing", "Full", "Full"]);
+        let (imported_id, imported) = model.get_tournaments().last().unwrap();
+        assert_ne!(*imported_id, t);
+        check_references(imported);
+
+        let imported_ids = get_ids(imported);
+        assert!(imported_ids.iter().all(|id| !existing_ids.contains(id) && *id < model.next_id));
+        let mut unique_ids = imported_ids.clone();
+        unique_ids.sort();
+        unique_ids.dedup();
+        assert_eq!(unique_ids.len(), imported_ids.len());
+
+        // Everything is the same apart from the ids
+        let original = &model.get_tournaments()[&t];
+        for (stage, imported_stage) in original.stages.values().zip(imported.stages.values()) {
+            let get_results = |s: &Stage| s.get_standings().iter()
+                .map(|e| (s.teams[&e.team_id].name.clone(), e.wins, e.losses)).collect::<Vec<_>>();
+            assert_eq!(get_results(stage), get_results(imported_stage));
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::model::tournament::{MatchId, Stage, TeamId};
+
+/// The result of a single match from the point of view of one of the teams.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MatchResult {
+    Win, Loss, Draw,
+}
+
+/// One of a team's results, used to show their recent form.
+#[derive(Debug)]
+pub struct FormEntry {
+    pub match_id: MatchId,
+    pub opponent: TeamId,
+    pub result: MatchResult,
+}
+
+/// A run of consecutive identical results.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Streak {
+    pub result: MatchResult,
+    pub length: usize,
+}
+
+impl MatchResult {
+    pub fn to_char(&self) -> char {
+        match self {
+            MatchResult::Win => 'W',
+            MatchResult::Loss => 'L',
+            MatchResult::Draw => 'D',
+        }
+    }
+}
+
+impl Streak {
+    /// Describes the streak for display, e.g. "W3".
+    pub fn get_desc(&self) -> String {
+        format!("{}{}", self.result.to_char(), self.length)
+    }
+}
+
+impl Stage {
+    /// Gets all the results for the given team in chronological order, i.e. the order of the stage's matches.
+    /// Tiebreaker matches aren't included.
+    pub fn get_form(&self, team_id: TeamId) -> Vec<FormEntry> {
+        self.get_regular_matches().filter(|m| m.contains(team_id)).map(|m| {
+            let opponent = if m.team_a == team_id { m.team_b } else { m.team_a };
+            let result = if m.get_winner() == Some(team_id) {
+                MatchResult::Win
+            } else if m.get_loser() == Some(team_id) {
+                MatchResult::Loss
+            } else {
+                MatchResult::Draw
+            };
+            FormEntry { match_id: m.id, opponent, result }
+        }).collect()
+    }
+}
+
+/// Gets the run of identical results at the end of the given form, i.e. the streak that the team is currently on.
+pub fn get_current_streak(form: &[FormEntry]) -> Option<Streak> {
+    let last = form.last()?;
+    let length = form.iter().rev().take_while(|f| f.result == last.result).count();
+    Some(Streak { result: last.result, length })
+}
+
+/// Gets the longest run of the given result anywhere in the given form.
+pub fn get_longest_streak(form: &[FormEntry], result: MatchResult) -> Option<Streak> {
+    let mut longest = 0;
+    let mut current = 0;
+    for f in form {
+        if f.result == result {
+            current += 1;
+            longest = std::cmp::max(longest, current);
+        } else {
+            current = 0;
+        }
+    }
+    if longest > 0 { Some(Streak { result, length: longest }) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::model::form::{get_current_streak, get_longest_streak, FormEntry, MatchResult, Streak};
+    use crate::model::tournament::SeriesFormat;
+
+    fn to_string(form: &[FormEntry]) -> String {
+        form.iter().map(|f| f.result.to_char()).collect()
+    }
+
+    #[test]
+    fn form_and_streaks() {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 1).unwrap();
+        // Two games are always played, so 1-1 is a draw
+        model.set_stage_series_format(t, s, SeriesFormat::FixedGames(2)).unwrap();
+        let [a, b, c, d] = ["A", "B", "C", "D"].map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        for (team_a, team_b, team_a_score, team_b_score) in [(a, b, 2, 0), (c, a, 0, 2), (a, d, 1, 1), (a, b, 2, 0), (a, c, 2, 0), (d, a, 0, 2)] {
+            model.add_match(t, s, team_a, team_b, team_a_score, team_b_score).unwrap();
+        }
+        let loss = model.add_match(t, s, b, a, 2, 0).unwrap();
+        // Tiebreaker matches aren't part of the form
+        model.add_tiebreaker_match(t, s, a, b, 2, 0).unwrap();
+
+        let form = model.get_stage(t, s).unwrap().get_form(a);
+        assert_eq!(to_string(&form), "WWDWWWL");
+        assert_eq!(form.iter().map(|f| f.opponent).collect::<Vec<_>>(), vec![b, c, d, b, c, d, b]);
+        assert_eq!(get_current_streak(&form), Some(Streak { result: MatchResult::Loss, length: 1 }));
+        // The draw breaks up the wins
+        assert_eq!(get_longest_streak(&form, MatchResult::Win), Some(Streak { result: MatchResult::Win, length: 3 }));
+        assert_eq!(get_longest_streak(&form, MatchResult::Draw), Some(Streak { result: MatchResult::Draw, length: 1 }));
+        assert_eq!(get_longest_streak(&form, MatchResult::Win).unwrap().get_desc(), "W3");
+
+        // The form is in the order of the matches, so moving the loss to the start changes it
+        model.reorder_match(t, s, loss, 0).unwrap();
+        let form = model.get_stage(t, s).unwrap().get_form(a);
+        assert_eq!(to_string(&form), "LWWDWWW");
+        assert_eq!(get_current_streak(&form), Some(Streak { result: MatchResult::Win, length: 3 }));
+        assert_eq!(get_longest_streak(&form, MatchResult::Loss), Some(Streak { result: MatchResult::Loss, length: 1 }));
+
+        let form = model.get_stage(t, s).unwrap().get_form(c);
+        assert_eq!(to_string(&form), "LL");
+        assert_eq!(get_longest_streak(&form, MatchResult::Win), None);
+    }
+
+    #[test]
+    fn no_matches() {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 1).unwrap();
+        let a = model.add_team(t, s, "A".to_string()).unwrap();
+        let form = model.get_stage(t, s).unwrap().get_form(a);
+        assert!(form.is_empty());
+        assert_eq!(get_current_streak(&form), None);
+        assert_eq!(get_longest_streak(&form, MatchResult::Win), None);
+    }
+}
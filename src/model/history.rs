@@ -0,0 +1,149 @@
+use indexmap::IndexMap;
+
+use crate::model::Model;
+use crate::model::tournament::{Tournament, TournamentId};
+
+// Undo and redo work by keeping a copy of all the tournaments from before each change. A change is anything that happens
+// between two calls to process_updates, so that e.g. deleting a team and all its matches is undone in one go.
+// The ids aren't rolled back, so anything created after undoing won't reuse an id that the UI might still know about.
+
+/// The copies of the tournaments which can be gone back (or forward) to. See history.rs.
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    undo_stack: Vec<IndexMap<TournamentId, Tournament>>,
+    redo_stack: Vec<IndexMap<TournamentId, Tournament>>,
+    /// The tournaments as of the last call to process_updates, which will be pushed onto the undo stack
+    /// if anything is changed before the next one.
+    current: IndexMap<TournamentId, Tournament>,
+    /// Set when the tournaments were changed by going through the history itself, so that this doesn't get
+    /// recorded as a new change.
+    up_to_date: bool,
+}
+
+impl Model {
+    /// The most changes which can be undone. Older ones are forgotten, to limit how much memory is used.
+    const MAX_UNDO: usize = 100;
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo_stack.is_empty()
+    }
+
+    /// Puts all the tournaments back to how they were before the last change.
+    pub fn undo(&mut self) -> Result<(), ()> {
+        match self.history.undo_stack.pop() {
+            Some(previous) => {
+                let undone = self.restore_history(previous);
+                self.history.redo_stack.push(undone);
+                Ok(())
+            }
+            None => Err(()),
+        }
+    }
+
+    /// Makes the last undone change again.
+    pub fn redo(&mut self) -> Result<(), ()> {
+        match self.history.redo_stack.pop() {
+            Some(next) => {
+                let redone = self.restore_history(next);
+                self.history.undo_stack.push(redone);
+                Ok(())
+            }
+            None => Err(()),
+        }
+    }
+
+    /// Forgets all the changes, so that nothing can be undone (or redone) from here.
+    /// This is for when the tournaments are replaced wholesale, e.g. when loading or going in and out of what-if mode.
+    /// Any changes which haven't been through process_updates yet are counted as part of the starting point.
+    pub(crate) fn reset_history(&mut self) {
+        self.history = History { current: self.tournaments.clone(), up_to_date: !self.changed_tournaments.is_empty(), ..Default::default() };
+    }
+
+    /// Called from process_updates, to remember how things were before any changes since last time.
+    pub(crate) fn record_history(&mut self) {
+        let up_to_date = std::mem::take(&mut self.history.up_to_date);
+        if up_to_date || self.changed_tournaments.is_empty() {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.history.current, self.tournaments.clone());
+        self.history.undo_stack.push(previous);
+        if self.history.undo_stack.len() > Model::MAX_UNDO {
+            self.history.undo_stack.remove(0);
+        }
+        self.history.redo_stack.clear();
+    }
+
+    /// Replaces the tournaments with some from the history, returning the ones that were replaced.
+    fn restore_history(&mut self, tournaments: IndexMap<TournamentId, Tournament>) -> IndexMap<TournamentId, Tournament> {
+        // Anything which is added or removed needs updating, as well as anything that was changed
+        self.changed_tournaments.extend(self.tournaments.keys());
+        self.changed_tournaments.extend(tournaments.keys());
+        let replaced = std::mem::replace(&mut self.tournaments, tournaments);
+        self.history.current = self.tournaments.clone();
+        self.history.up_to_date = true;
+        replaced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{Model, ModelObserver};
+    use crate::model::tournament::TournamentId;
+
+    /// Stands in for the UI, remembering which tournaments it was told about.
+    #[derive(Default)]
+    struct Observer {
+        changed: Vec<TournamentId>,
+    }
+
+    impl ModelObserver for Observer {
+        fn tournament_changed(&mut self, _model: &Model, tournament_id: TournamentId) {
+            self.changed.push(tournament_id);
+        }
+    }
+
+    fn get_name(model: &Model, id: TournamentId) -> &str {
+        &model.get_tournament(id).unwrap().name
+    }
+
+    #[test]
+    fn undo_redo_through_process_updates() {
+        let mut model = Model::new();
+        let mut observer = Observer::default();
+        let t = model.add_tournament("First".to_string());
+        model.process_updates(&mut observer);
+        model.rename_tournament(t, "Second").unwrap();
+        model.process_updates(&mut observer);
+        assert!(model.can_undo() && !model.can_redo());
+
+        // The observers are told about the undo, and it isn't recorded as another change that can be undone
+        observer.changed.clear();
+        model.undo().unwrap();
+        model.process_updates(&mut observer);
+        assert!(observer.changed.contains(&t));
+        assert_eq!(get_name(&model, t), "First");
+        assert!(model.can_undo() && model.can_redo());
+
+        // Nothing changing in between doesn't lose the redo
+        model.process_updates(&mut observer);
+        model.redo().unwrap();
+        model.process_updates(&mut observer);
+        assert_eq!(get_name(&model, t), "Second");
+        assert!(model.can_undo() && !model.can_redo());
+
+        // Going all the way back removes the tournament, and making a new change after undoing means there's nothing to redo
+        model.undo().unwrap();
+        model.process_updates(&mut observer);
+        model.undo().unwrap();
+        model.process_updates(&mut observer);
+        assert!(model.get_tournament(t).is_none());
+        assert!(!model.can_undo() && model.undo().is_err());
+        model.add_tournament("Other".to_string());
+        model.process_updates(&mut observer);
+        assert!(model.can_undo() && !model.can_redo() && model.redo().is_err());
+    }
+}
@@ -6,9 +6,12 @@ pub(crate) mod v1 {
     use indexmap::indexmap;
     use log::debug;
     use serde::{Serialize, Deserialize};
-    use web_sys::Storage;
 
+    use crate::model::storage::StorageBackend;
+    use crate::model::tournament::ScoringSystem;
+    use crate::model::tournament::SeriesFormat;
     use crate::model::tournament::StageKind;
+    use crate::model::tournament::Tiebreaker;
 
     #[derive(Serialize, Deserialize, Debug)]
     struct Model {
@@ -52,7 +55,7 @@ pub(crate) mod v1 {
         loser: TeamId,
     }
 
-    pub fn load_and_upgrade(storage: Storage) -> Result<crate::model::Model, String> {
+    pub fn load_and_upgrade(storage: &dyn StorageBackend) -> Result<crate::model::Model, String> {
         debug!("Loading and upgrading model from v1");
 
         let old_model = match storage.get_item("tournament-tracker-model") {
@@ -62,7 +65,7 @@ pub(crate) mod v1 {
                     Err(e) => Err(format!("Failed to deserialize data: {:?}", e)),
                 }
             }
-            e => Err(format!("Failed to load from local storage: {:?}", e)),
+            e => Err(format!("Failed to load from storage: {:?}", e)),
         }?;
 
         let mut new_model = crate::model::Model::new();
@@ -71,7 +74,9 @@ pub(crate) mod v1 {
             let mut new_tournament = crate::model::Tournament { id: old_tournament.id, name: old_tournament.name, stages: indexmap!{} };
 
             for (stage_id, old_stage) in old_tournament.stages {
-                let mut new_stage = crate::model::Stage { id: old_stage.id, name: old_stage.name, tournament_id, teams: indexmap!{}, matches: indexmap!{}, kind: StageKind::RoundRobin {  } };
+                let mut new_stage = crate::model::Stage { id: old_stage.id, name: old_stage.name, tournament_id, teams: indexmap!{}, matches: indexmap!{}, kind: StageKind::RoundRobin { legs: 1, schedule: vec![], round_labels: indexmap!{} },
+                    tiebreakers: Tiebreaker::defaults(), series_format: SeriesFormat::BestOf(1),
+                    scoring_system: ScoringSystem::WinLoss, advancing_teams: 0 };
 
                 for (team_id, old_team) in old_stage.teams {
                     let new_team = crate::model::Team { id: old_team.id, name: old_team.name };
@@ -84,6 +89,7 @@ pub(crate) mod v1 {
                     let new_match = crate::model::Match { id: old_match.id, team_a: old_match.teams[0],
                         team_b: old_match.teams[1], team_a_score: if old_match.winner == old_match.teams[0] { 1 } else { 0 },
                         team_b_score: if old_match.winner == old_match.teams[1] { 1 } else { 0 },
+                        is_tiebreaker: false,
                     };
 
                     new_stage.matches.insert(match_id, new_match);
@@ -105,7 +111,8 @@ pub(crate) mod v2 {
     use indexmap::indexmap;
     use log::debug;
     use serde::{Serialize, Deserialize};
-    use web_sys::Storage;
+
+    use crate::model::storage::StorageBackend;
 
     #[derive(Serialize, Deserialize, Debug)]
     struct Model {
@@ -193,7 +200,7 @@ pub(crate) mod v2 {
         Loser(FixtureId)
     }
 
-    pub fn load_and_upgrade(storage: Storage) -> Result<crate::model::Model, String> {
+    pub fn load_and_upgrade(storage: &dyn StorageBackend) -> Result<crate::model::Model, String> {
         debug!("Loading and upgrading model from v2");
 
         let old_model = match storage.get_item("tournament-tracker-model") {
@@ -203,7 +210,7 @@ pub(crate) mod v2 {
                     Err(e) => Err(format!("Failed to deserialize data: {:?}", e)),
                 }
             }
-            e => Err(format!("Failed to load from local storage: {:?}", e)),
+            e => Err(format!("Failed to load from storage: {:?}", e)),
         }?;
 
         let mut new_model = crate::model::Model::new();
@@ -213,7 +220,7 @@ pub(crate) mod v2 {
 
             for (stage_id, old_stage) in old_tournament.stages {
                 let new_kind = match old_stage.kind {
-                    StageKind::RoundRobin {  } => crate::model::StageKind::RoundRobin {  },
+                    StageKind::RoundRobin {  } => crate::model::StageKind::RoundRobin { legs: 1, schedule: vec![], round_labels: indexmap!{} },
                     StageKind::Bracket { fixtures: old_fixtures } => {
                         let mut new_fixtures = indexmap!{};
                         for (fixture_id, old_fixture) in old_fixtures {
@@ -226,7 +233,7 @@ pub(crate) mod v2 {
                             };
 
                             let new_fixture = crate::model::Fixture { id: old_fixture.id, layout: old_fixture.layout, match_id: old_fixture.match_id,
-                                team_a: upgrade_fixture_team(old_fixture.team_a), team_b: upgrade_fixture_team(old_fixture.team_b) };
+                                team_a: upgrade_fixture_team(old_fixture.team_a), team_b: upgrade_fixture_team(old_fixture.team_b), exits: vec![] };
 
                             new_fixtures.insert(fixture_id, new_fixture);
                         }
@@ -235,7 +242,9 @@ pub(crate) mod v2 {
                     }
                 };
 
-                let mut new_stage = crate::model::Stage { id: old_stage.id, name: old_stage.name, tournament_id, teams: indexmap!{}, matches: indexmap!{}, kind: new_kind };
+                let mut new_stage = crate::model::Stage { id: old_stage.id, name: old_stage.name, tournament_id, teams: indexmap!{}, matches: indexmap!{}, kind: new_kind,
+                    tiebreakers: crate::model::tournament::Tiebreaker::defaults(), series_format: crate::model::tournament::SeriesFormat::BestOf(1),
+                    scoring_system: crate::model::tournament::ScoringSystem::WinLoss, advancing_teams: 0 };
 
                 for (team_id, old_team) in old_stage.teams {
                     let new_team = crate::model::Team { id: old_team.id, name: old_team.name };
@@ -246,6 +255,7 @@ pub(crate) mod v2 {
                 for (match_id, old_match) in old_stage.matches {
                     let new_match = crate::model::Match { id: old_match.id, team_a: old_match.team_a,
                         team_b: old_match.team_b, team_a_score: old_match.team_a_score, team_b_score: old_match.team_b_score,
+                        is_tiebreaker: false,
                     };
 
                     new_stage.matches.insert(match_id, new_match);
@@ -259,4 +269,205 @@ pub(crate) mod v2 {
 
         Ok(new_model)
     }
-}
\ No newline at end of file
+}
+// V3 didn't have tiebreaker matches, which v4 records on each Match
+pub(crate) mod v3 {
+    use indexmap::IndexMap;
+    use indexmap::indexmap;
+    use log::debug;
+    use serde::{Serialize, Deserialize};
+
+    use crate::model::storage::StorageBackend;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Model {
+        tournaments: IndexMap<TournamentId, Tournament>,
+        next_id: usize,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Tournament {
+        pub id: TournamentId,
+        pub name: String,
+        pub stages: IndexMap<StageId, Stage>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Stage  {
+        pub id: StageId,
+        pub tournament_id: TournamentId,
+        pub name: String,
+
+        pub teams: IndexMap<TeamId, Team>,
+        pub matches: IndexMap<MatchId, Match>,
+        #[serde(default = "default_stage_kind_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+        pub kind: StageKind,
+        #[serde(default = "default_tiebreakers_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+        pub tiebreakers: Vec<Tiebreaker>,
+    }
+
+    // This field was added, so give it a default value so that we can deserialize old data
+    fn default_stage_kind_for_deserialization() -> StageKind {
+        StageKind::RoundRobin { legs: 1 }
+    }
+
+    // This field was added, so give it a default value so that we can deserialize old data
+    fn default_tiebreakers_for_deserialization() -> Vec<Tiebreaker> {
+        vec![Tiebreaker::HeadToHead, Tiebreaker::HeadToHeadMiniTable, Tiebreaker::ScoreDifferential, Tiebreaker::StrengthOfVictory]
+    }
+
+    // This field was added, so give it a default value so that we can deserialize old data
+    fn default_legs_for_deserialization() -> u32 {
+        1
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum StageKind {
+        RoundRobin {
+            #[serde(default = "default_legs_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+            legs: u32,
+        },
+        Bracket {
+            fixtures: IndexMap<FixtureId, Fixture>,
+        }
+    }
+
+    pub type TournamentId = usize;
+    pub type StageId = usize;
+    pub type TeamId = usize;
+    pub type MatchId = usize;
+    pub type FixtureId = usize;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Team {
+        pub id: TeamId,
+        pub name: String,
+    }
+
+    /// A match is something that we already have the results for. See also Fixture.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Match {
+        pub id: MatchId,
+        pub team_a: TeamId,
+        pub team_b: TeamId,
+        pub team_a_score: u32,
+        pub team_b_score: u32,
+    }
+
+    /// A fixture is a match that might not yet have been played, used to describe an elimination bracket.
+    /// See also Match.
+    #[derive(Serialize, Deserialize, Debug)]
+    pub struct Fixture {
+        pub id: FixtureId,
+        /// The position of this fixture on the bracket view.
+        pub layout: (i32, i32),
+        /// If this fixture has already been played, this links to the match results.
+        pub match_id: Option<MatchId>,
+        /// If the team(s) playing in this fixture are determined by the results of a previous fixture,
+        /// that is recorded here. E.g. in an elimination bracket the winner will advance to the next fixture.
+        pub team_a: FixtureTeam,
+        pub team_b: FixtureTeam,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum FixtureTeam {
+        /// The team playing in this fixture is fixed, i.e. pre-determined and not based on the result of another fixture.
+        Fixed(TeamId),
+        /// The team playing in this fixture is the winner/loser of a previous fixture in the stage.
+        Linked {
+            fixture_id: FixtureId,
+            outcome: Outcome,
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Outcome {
+        Winner, Loser,
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    pub enum Tiebreaker {
+        HeadToHead,
+        HeadToHeadMiniTable,
+        ScoreDifferential,
+        StrengthOfVictory,
+    }
+
+    pub fn load_and_upgrade(storage: &dyn StorageBackend) -> Result<crate::model::Model, String> {
+        debug!("Loading and upgrading model from v3");
+
+        let old_model = match storage.get_item("tournament-tracker-model") {
+            Ok(Some(s)) => {
+                match serde_json::from_str::<Model>(&s) {
+                    Ok(m) => Ok(m),
+                    Err(e) => Err(format!("Failed to deserialize data: {:?}", e)),
+                }
+            }
+            e => Err(format!("Failed to load from storage: {:?}", e)),
+        }?;
+
+        let mut new_model = crate::model::Model::new();
+        new_model.next_id = old_model.next_id;
+        for (tournament_id, old_tournament) in old_model.tournaments {
+            let mut new_tournament = crate::model::Tournament { id: old_tournament.id, name: old_tournament.name, stages: indexmap!{} };
+
+            for (stage_id, old_stage) in old_tournament.stages {
+                let new_kind = match old_stage.kind {
+                    StageKind::RoundRobin { legs } => crate::model::StageKind::RoundRobin { legs, schedule: vec![], round_labels: indexmap!{} },
+                    StageKind::Bracket { fixtures: old_fixtures } => {
+                        let mut new_fixtures = indexmap!{};
+                        for (fixture_id, old_fixture) in old_fixtures {
+                            let upgrade_fixture_team = |ft: FixtureTeam| {
+                                match ft {
+                                    FixtureTeam::Fixed(t) => crate::model::FixtureTeam::Fixed(t),
+                                    FixtureTeam::Linked { fixture_id, outcome: Outcome::Winner } => crate::model::FixtureTeam::Linked { fixture_id, outcome: crate::model::tournament::Outcome::Winner },
+                                    FixtureTeam::Linked { fixture_id, outcome: Outcome::Loser } => crate::model::FixtureTeam::Linked { fixture_id, outcome: crate::model::tournament::Outcome::Loser },
+                                }
+                            };
+
+                            let new_fixture = crate::model::Fixture { id: old_fixture.id, layout: old_fixture.layout, match_id: old_fixture.match_id,
+                                team_a: upgrade_fixture_team(old_fixture.team_a), team_b: upgrade_fixture_team(old_fixture.team_b), exits: vec![] };
+
+                            new_fixtures.insert(fixture_id, new_fixture);
+                        }
+
+                        crate::model::StageKind::Bracket { fixtures: new_fixtures }
+                    }
+                };
+
+                let new_tiebreakers = old_stage.tiebreakers.into_iter().map(|t| match t {
+                    Tiebreaker::HeadToHead => crate::model::tournament::Tiebreaker::HeadToHead,
+                    Tiebreaker::HeadToHeadMiniTable => crate::model::tournament::Tiebreaker::HeadToHeadMiniTable,
+                    Tiebreaker::ScoreDifferential => crate::model::tournament::Tiebreaker::ScoreDifferential,
+                    Tiebreaker::StrengthOfVictory => crate::model::tournament::Tiebreaker::StrengthOfVictory,
+                }).collect();
+
+                let mut new_stage = crate::model::Stage { id: old_stage.id, name: old_stage.name, tournament_id, teams: indexmap!{}, matches: indexmap!{}, kind: new_kind,
+                    tiebreakers: new_tiebreakers, series_format: crate::model::tournament::SeriesFormat::BestOf(1),
+                    scoring_system: crate::model::tournament::ScoringSystem::WinLoss, advancing_teams: 0 };
+
+                for (team_id, old_team) in old_stage.teams {
+                    let new_team = crate::model::Team { id: old_team.id, name: old_team.name };
+
+                    new_stage.teams.insert(team_id, new_team);
+                }
+
+                for (match_id, old_match) in old_stage.matches {
+                    // This is where the actual conversion happens from v3 -> v4. There were no tiebreaker matches before.
+                    let new_match = crate::model::Match { id: old_match.id, team_a: old_match.team_a,
+                        team_b: old_match.team_b, team_a_score: old_match.team_a_score, team_b_score: old_match.team_b_score,
+                        is_tiebreaker: false,
+                    };
+
+                    new_stage.matches.insert(match_id, new_match);
+                }
+
+                new_tournament.stages.insert(stage_id, new_stage);
+            }
+
+            new_model.tournaments.insert(tournament_id, new_tournament);
+        }
+
+        Ok(new_model)
+    }
+}
@@ -0,0 +1,353 @@
+use crate::model::Model;
+use crate::model::tournament::{BracketExit, FixtureTeam, Outcome, StageId, StageKind, TeamId, TournamentId};
+
+// Generators for common bracket formats, so that the fixtures don't all need creating and linking by hand.
+// These only add fixtures to an existing (empty) bracket stage, using teams which are already in the stage.
+
+/// Horizontal distance between the fixtures of consecutive rounds.
+const COLUMN_WIDTH: i32 = 250;
+/// Vertical distance between the fixtures of the first round.
+const ROW_HEIGHT: i32 = 140;
+/// Gap between the edge of the view and the first fixtures.
+const MARGIN: i32 = 20;
+
+/// Something going into a fixture of a generated bracket, along with the vertical position that it comes from.
+/// The team is None for a bye, i.e. when there aren't enough teams to fill the bracket.
+type Slot = (Option<FixtureTeam>, i32);
+
+impl Model {
+    /// Creates a single-elimination bracket for the given teams, which are in seed order (best first).
+    /// If the number of teams isn't a power of two, the top seeds get byes through the first round.
+    /// The third place match is played between the losers of the semi-finals, so needs at least four teams.
+    pub fn generate_single_elimination(&mut self, tournament_id: TournamentId, stage_id: StageId, seeded_teams: &[TeamId], third_place_match: bool) -> Result<(), ()> {
+        if !self.can_generate_bracket(tournament_id, stage_id, seeded_teams) || (third_place_match && seeded_teams.len() < 4) {
+            return Err(());
+        }
+
+        let mut slots = get_first_round_slots(seeded_teams, MARGIN);
+        let mut round = 0;
+        let mut semi_final_losers = vec![];
+        while slots.len() > 1 {
+            let (winners, losers) = self.add_bracket_round(tournament_id, stage_id, MARGIN + round * COLUMN_WIDTH, &slots)?;
+            if winners.len() == 2 {
+                semi_final_losers = losers;
+            }
+            slots = winners;
+            round += 1;
+        }
+
+        if third_place_match {
+            if let [(a, _), (b, _)] = semi_final_losers[..] {
+                let final_y = slots[0].1;
+                self.add_fixture_or_bye(tournament_id, stage_id, (MARGIN + (round - 1) * COLUMN_WIDTH, final_y + ROW_HEIGHT * 3 / 2), a, b)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates a double-elimination bracket for the given teams, which are in seed order (best first).
+    /// Losers in the upper bracket drop into the lower bracket, and the winners of each bracket meet in the grand final.
+    /// The bracket reset is a second grand final, which only needs playing if the lower bracket team wins the first one.
+    pub fn generate_double_elimination(&mut self, tournament_id: TournamentId, stage_id: StageId, seeded_teams: &[TeamId], bracket_reset: bool) -> Result<(), ()> {
+        if !self.can_generate_bracket(tournament_id, stage_id, seeded_teams) || seeded_teams.len() < 3 {
+            return Err(());
+        }
+
+        // Upper bracket along the top
+        let mut upper_slots = get_first_round_slots(seeded_teams, MARGIN);
+        let lower_bracket_top = upper_slots.last().map(|s| s.1).unwrap_or(0) + ROW_HEIGHT * 2;
+        let mut upper_losers_per_round = vec![];
+        let mut upper_round = 0;
+        while upper_slots.len() > 1 {
+            let (winners, losers) = self.add_bracket_round(tournament_id, stage_id, MARGIN + upper_round * COLUMN_WIDTH, &upper_slots)?;
+            upper_losers_per_round.push(losers);
+            upper_slots = winners;
+            upper_round += 1;
+        }
+
+        // Lower bracket underneath. The first round is between the losers of the upper bracket's first round, then after that
+        // the rounds alternate between the lower bracket survivors playing the next batch of teams to drop down from the
+        // upper bracket, and the survivors playing each other.
+        let mut upper_losers_per_round = upper_losers_per_round.into_iter();
+        let first_losers: Vec<Slot> = upper_losers_per_round.next().unwrap_or_default().into_iter().enumerate()
+            .map(|(i, (t, _))| (t, lower_bracket_top + i as i32 * ROW_HEIGHT / 2)).collect();
+        let (mut lower_slots, _) = self.add_bracket_round(tournament_id, stage_id, MARGIN, &first_losers)?;
+        let mut lower_round = 1;
+        for (r, dropping) in upper_losers_per_round.enumerate() {
+            // Alternate the order that teams drop down in, to make it less likely that they play the same team again straight away
+            let dropping: Vec<Slot> = if r % 2 == 0 { dropping.into_iter().rev().collect() } else { dropping };
+            let x = MARGIN + lower_round * COLUMN_WIDTH;
+            let mut next_slots = vec![];
+            for ((survivor, y), (dropped, _)) in lower_slots.iter().zip(dropping) {
+                let (winner, _) = self.add_fixture_or_bye(tournament_id, stage_id, (x, *y), *survivor, dropped)?;
+                next_slots.push((winner, *y));
+            }
+            lower_slots = next_slots;
+            lower_round += 1;
+
+            if lower_slots.len() > 1 {
+                let (winners, _) = self.add_bracket_round(tournament_id, stage_id, MARGIN + lower_round * COLUMN_WIDTH, &lower_slots)?;
+                lower_slots = winners;
+                lower_round += 1;
+            }
+        }
+
+        // Grand final to the right of both brackets, between them
+        if let ([(upper_winner, upper_y)], [(lower_winner, lower_y)]) = (&upper_slots[..], &lower_slots[..]) {
+            let x = MARGIN + std::cmp::max(upper_round, lower_round) * COLUMN_WIDTH;
+            let y = (upper_y + lower_y) / 2;
+            let (winner, loser) = self.add_fixture_or_bye(tournament_id, stage_id, (x, y), *upper_winner, *lower_winner)?;
+            if bracket_reset {
+                self.add_fixture_or_bye(tournament_id, stage_id, (x + COLUMN_WIDTH, y), winner, loser)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates a four-team GSL group (also known as a dual tournament) for the given teams, which are in seed order (best first).
+    /// The winners of the opening matches play each other in the winners' match, and the losers play in the elimination match.
+    /// The loser of the winners' match then plays the winner of the elimination match in the decider.
+    /// The winners of the winners' match and the decider advance, and the losers of the elimination match and the decider are eliminated.
+    pub fn generate_gsl_group(&mut self, tournament_id: TournamentId, stage_id: StageId, seeded_teams: &[TeamId]) -> Result<(), ()> {
+        if !self.can_generate_bracket(tournament_id, stage_id, seeded_teams) || seeded_teams.len() != 4 {
+            return Err(());
+        }
+
+        let seed = |i: usize| Some(FixtureTeam::Fixed(seeded_teams[i]));
+        let (opening_a_winner, opening_a_loser) = self.add_fixture_or_bye(tournament_id, stage_id, (MARGIN, MARGIN), seed(0), seed(3))?;
+        let (opening_b_winner, opening_b_loser) = self.add_fixture_or_bye(tournament_id, stage_id, (MARGIN, MARGIN + ROW_HEIGHT), seed(1), seed(2))?;
+        let (winners_match_winner, winners_match_loser) = self.add_fixture_or_bye(tournament_id, stage_id, (MARGIN + COLUMN_WIDTH, MARGIN), opening_a_winner, opening_b_winner)?;
+        let (elimination_match_winner, elimination_match_loser) = self.add_fixture_or_bye(tournament_id, stage_id, (MARGIN + COLUMN_WIDTH, MARGIN + ROW_HEIGHT * 2), opening_a_loser, opening_b_loser)?;
+        let (decider_winner, decider_loser) = self.add_fixture_or_bye(tournament_id, stage_id, (MARGIN + COLUMN_WIDTH * 2, MARGIN + ROW_HEIGHT), winners_match_loser, elimination_match_winner)?;
+
+        // Record who goes out of the group and how, so that it can be shown once the results are in
+        for (team, exit) in [(winners_match_winner, BracketExit::Advance), (decider_winner, BracketExit::Advance),
+                (elimination_match_loser, BracketExit::Eliminate), (decider_loser, BracketExit::Eliminate)] {
+            if let Some(FixtureTeam::Linked { fixture_id, outcome }) = team {
+                self.set_fixture_exit(tournament_id, stage_id, fixture_id, outcome, Some(exit))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the stage is a bracket with no fixtures yet, and that the teams are all distinct and in the stage.
+    fn can_generate_bracket(&self, tournament_id: TournamentId, stage_id: StageId, teams: &[TeamId]) -> bool {
+        match self.get_stage(tournament_id, stage_id) {
+            Some(s) => {
+                let is_empty_bracket = matches!(&s.kind, StageKind::Bracket { fixtures } if fixtures.is_empty());
+                let all_in_stage = teams.iter().all(|t| s.teams.contains_key(t));
+                let all_distinct = teams.iter().enumerate().all(|(i, t)| !teams[..i].contains(t));
+                is_empty_bracket && all_in_stage && all_distinct && teams.len() >= 2
+            }
+            None => false,
+        }
+    }
+
+    /// Pairs up the given slots into fixtures, placed in a column at the given x position.
+    /// Returns the winners and losers of the round, in the same order as the fixtures.
+    fn add_bracket_round(&mut self, tournament_id: TournamentId, stage_id: StageId, x: i32, slots: &[Slot]) -> Result<(Vec<Slot>, Vec<Slot>), ()> {
+        let mut winners = vec![];
+        let mut losers = vec![];
+        for pair in slots.chunks(2) {
+            // Half way between the two things that feed into this fixture
+            let y = (pair[0].1 + pair.get(1).unwrap_or(&pair[0]).1) / 2;
+            let (winner, loser) = self.add_fixture_or_bye(tournament_id, stage_id, (x, y), pair[0].0, pair.get(1).and_then(|s| s.0))?;
+            winners.push((winner, y));
+            losers.push((loser, y));
+        }
+        Ok((winners, losers))
+    }
+
+    /// Adds a fixture between the two given teams, unless one of them is a bye in which case the other goes straight through.
+    /// Returns the winner and loser.
+    fn add_fixture_or_bye(&mut self, tournament_id: TournamentId, stage_id: StageId, layout: (i32, i32), a: Option<FixtureTeam>, b: Option<FixtureTeam>)
+            -> Result<(Option<FixtureTeam>, Option<FixtureTeam>), ()> {
+        match (a, b) {
+            (Some(a), Some(b)) => {
+                let fixture_id = self.add_fixture(tournament_id, stage_id, layout, a, b).ok_or(())?;
+                Ok((Some(FixtureTeam::Linked { fixture_id, outcome: Outcome::Winner }), Some(FixtureTeam::Linked { fixture_id, outcome: Outcome::Loser })))
+            }
+            (Some(t), None) | (None, Some(t)) => Ok((Some(t), None)),
+            (None, None) => Ok((None, None)),
+        }
+    }
+}
+
+/// Gets the teams going into the first round of a bracket, in bracket order, with byes filling in for the missing teams.
+/// The first fixture is at the given y position.
+fn get_first_round_slots(seeded_teams: &[TeamId], top: i32) -> Vec<Slot> {
+    get_seeding_order(seeded_teams.len().next_power_of_two()).into_iter().enumerate()
+        .map(|(i, seed)| (seeded_teams.get(seed - 1).map(|t| FixtureTeam::Fixed(*t)), top + i as i32 * ROW_HEIGHT / 2)).collect()
+}
+
+/// The standard order of seeds in a bracket, so that the top seeds can't meet until the later rounds,
+/// e.g. [1, 8, 4, 5, 2, 7, 3, 6] for 8 teams (where 1 plays 8, 4 plays 5 etc.). The size must be a power of two.
+fn get_seeding_order(size: usize) -> Vec<usize> {
+    let mut result = vec![1];
+    while result.len() < size {
+        let n = result.len() * 2;
+        result = result.iter().flat_map(|&s| [s, n + 1 - s]).collect();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::model::tournament::{Fixture, FixtureId, FixtureTeam, Outcome, StageId, StageKind, TeamId, TournamentId};
+
+    use super::get_seeding_order;
+
+    /// Creates a model with an empty bracket stage containing the given number of teams, which are returned in seed order.
+    fn new_bracket(num_teams: usize) -> (Model, TournamentId, StageId, Vec<TeamId>) {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_bracket(t, "Playoffs".to_string()).unwrap();
+        let teams = (0..num_teams).map(|i| model.add_team(t, s, format!("Seed {}", i + 1)).unwrap()).collect();
+        (model, t, s, teams)
+    }
+
+    fn get_fixtures(model: &Model, t: TournamentId, s: StageId) -> Vec<Fixture> {
+        match &model.get_stage(t, s).unwrap().kind {
+            StageKind::Bracket { fixtures } => fixtures.values().cloned().collect(),
+            _ => panic!("Expected a bracket"),
+        }
+    }
+
+    /// How many fixture inputs take the team with the given outcome from the given fixture.
+    fn count_links(fixtures: &[Fixture], fixture_id: FixtureId, outcome: Outcome) -> usize {
+        fixtures.iter().flat_map(|f| [f.team_a, f.team_b]).filter(|t| *t == FixtureTeam::Linked { fixture_id, outcome }).count()
+    }
+
+    /// Enters a result for every fixture, in whatever order they become playable, with a mix of team A and team B winning.
+    /// Returns the number of losses of each team.
+    fn play_out(model: &mut Model, t: TournamentId, s: StageId, teams: &[TeamId]) -> Vec<usize> {
+        loop {
+            let stage = model.get_stage(t, s).unwrap();
+            let playable = get_fixtures(model, t, s).into_iter().find(|f| f.match_id.is_none()
+                && stage.get_fixture_team(&f.team_a).is_some() && stage.get_fixture_team(&f.team_b).is_some());
+            match playable {
+                Some(f) => {
+                    let score = if f.id % 3 == 0 { (0, 1) } else { (1, 0) };
+                    model.set_fixture_score(t, s, f.id, score.0, score.1).unwrap();
+                }
+                None => break,
+            }
+        }
+        let stage = model.get_stage(t, s).unwrap();
+        teams.iter().map(|t| stage.matches.values().filter(|m| m.get_loser() == Some(*t)).count()).collect()
+    }
+
+    #[test]
+    fn seeding_order() {
+        assert_eq!(get_seeding_order(1), vec![1]);
+        assert_eq!(get_seeding_order(2), vec![1, 2]);
+        assert_eq!(get_seeding_order(4), vec![1, 4, 2, 3]);
+        assert_eq!(get_seeding_order(8), vec![1, 8, 4, 5, 2, 7, 3, 6]);
+    }
+
+    #[test]
+    fn single_elimination_with_third_place_match() {
+        let (mut model, t, s, teams) = new_bracket(4);
+        model.generate_single_elimination(t, s, &teams, true).unwrap();
+        let fixtures = get_fixtures(&model, t, s);
+        assert_eq!(fixtures.len(), 4);
+
+        // 1 v 4 and 2 v 3, then the final and the third place match between the semi-final winners and losers
+        let (semi_1, semi_2) = (&fixtures[0], &fixtures[1]);
+        assert_eq!((semi_1.team_a, semi_1.team_b), (FixtureTeam::Fixed(teams[0]), FixtureTeam::Fixed(teams[3])));
+        assert_eq!((semi_2.team_a, semi_2.team_b), (FixtureTeam::Fixed(teams[1]), FixtureTeam::Fixed(teams[2])));
+        for (outcome, f) in [(Outcome::Winner, &fixtures[2]), (Outcome::Loser, &fixtures[3])] {
+            assert_eq!(f.team_a, FixtureTeam::Linked { fixture_id: semi_1.id, outcome });
+            assert_eq!(f.team_b, FixtureTeam::Linked { fixture_id: semi_2.id, outcome });
+        }
+
+        let losses = play_out(&mut model, t, s, &teams);
+        assert_eq!(get_fixtures(&model, t, s).iter().filter(|f| f.match_id.is_some()).count(), 4);
+        // Everyone except the winner loses once in the semi-finals or final, and the third place match is a second loss for one team
+        assert_eq!(losses.iter().sum::<usize>(), 4);
+        assert_eq!(losses.iter().filter(|l| **l == 0).count(), 1);
+    }
+
+    #[test]
+    fn single_elimination_with_byes() {
+        let (mut model, t, s, teams) = new_bracket(5);
+        model.generate_single_elimination(t, s, &teams, false).unwrap();
+        let fixtures = get_fixtures(&model, t, s);
+        // Only 4 v 5 is played in the first round, so one fixture fewer than the number of teams
+        assert_eq!(fixtures.len(), 4);
+        assert_eq!((fixtures[0].team_a, fixtures[0].team_b), (FixtureTeam::Fixed(teams[3]), FixtureTeam::Fixed(teams[4])));
+        // The top seed plays the winner of that
+        assert!(fixtures.iter().any(|f| f.team_a == FixtureTeam::Fixed(teams[0])
+            && f.team_b == FixtureTeam::Linked { fixture_id: fixtures[0].id, outcome: Outcome::Winner }));
+
+        let losses = play_out(&mut model, t, s, &teams);
+        assert_eq!(losses.iter().filter(|l| **l == 0).count(), 1);
+        assert!(losses.iter().all(|l| *l <= 1));
+    }
+
+    /// Checks that a generated double-elimination bracket (without a bracket reset) gives every team two lives.
+    fn check_double_elimination(num_teams: usize) {
+        let (mut model, t, s, teams) = new_bracket(num_teams);
+        model.generate_double_elimination(t, s, &teams, false).unwrap();
+        let fixtures = get_fixtures(&model, t, s);
+        // Everyone except the winner loses twice
+        assert_eq!(fixtures.len(), 2 * num_teams - 2);
+
+        // Each winner and loser goes on to at most one other fixture
+        for f in &fixtures {
+            assert!(count_links(&fixtures, f.id, Outcome::Winner) <= 1);
+            assert!(count_links(&fixtures, f.id, Outcome::Loser) <= 1);
+        }
+        // Every upper bracket loser drops into the lower bracket. The upper bracket is everything that only unbeaten teams play in.
+        let mut is_upper = vec![];
+        for f in &fixtures {
+            let unbeaten = |t: &FixtureTeam| match t {
+                FixtureTeam::Fixed(_) => true,
+                FixtureTeam::Linked { fixture_id, outcome } => *outcome == Outcome::Winner && is_upper.contains(fixture_id),
+            };
+            if unbeaten(&f.team_a) && unbeaten(&f.team_b) {
+                is_upper.push(f.id);
+                assert_eq!(count_links(&fixtures, f.id, Outcome::Loser), 1, "Loser of upper bracket fixture {} doesn't drop down", f.id);
+            }
+        }
+        assert_eq!(is_upper.len(), num_teams - 1);
+
+        let losses = play_out(&mut model, t, s, &teams);
+        assert!(get_fixtures(&model, t, s).iter().all(|f| f.match_id.is_some()));
+        // Nobody is knocked out before losing twice
+        assert_eq!(losses.iter().filter(|l| **l == 2).count(), num_teams - 1, "{losses:?}");
+        assert!(losses.iter().all(|l| *l <= 2), "{losses:?}");
+    }
+
+    #[test]
+    fn double_elimination_4_teams() {
+        check_double_elimination(4);
+    }
+
+    #[test]
+    fn double_elimination_8_teams() {
+        check_double_elimination(8);
+    }
+
+    #[test]
+    fn double_elimination_with_byes() {
+        check_double_elimination(5);
+        check_double_elimination(6);
+    }
+
+    #[test]
+    fn double_elimination_bracket_reset() {
+        let (mut model, t, s, teams) = new_bracket(4);
+        model.generate_double_elimination(t, s, &teams, true).unwrap();
+        let fixtures = get_fixtures(&model, t, s);
+        assert_eq!(fixtures.len(), 7);
+        // The reset is a rematch between the two teams in the grand final
+        let (grand_final, reset) = (&fixtures[5], &fixtures[6]);
+        assert_eq!(reset.team_a, FixtureTeam::Linked { fixture_id: grand_final.id, outcome: Outcome::Winner });
+        assert_eq!(reset.team_b, FixtureTeam::Linked { fixture_id: grand_final.id, outcome: Outcome::Loser });
+    }
+}
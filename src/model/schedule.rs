@@ -0,0 +1,150 @@
+use crate::model::Model;
+use crate::model::tournament::{ScheduledMatch, StageId, StageKind, TeamId, TournamentId};
+
+impl Model {
+    /// Creates a schedule for a round robin stage, so that every pair of teams plays once per leg.
+    /// Each round, every team plays once (or sits out, if there's an odd number of teams). Any matches which have already
+    /// been played are counted towards the schedule.
+    pub fn generate_round_robin_schedule(&mut self, tournament_id: TournamentId, stage_id: StageId) -> Result<(), ()> {
+        if let Some(s) = self.tournaments.get_mut(&tournament_id).and_then(|t| t.stages.get_mut(&stage_id)) {
+            let teams: Vec<TeamId> = s.teams.keys().copied().collect();
+            match &mut s.kind {
+                StageKind::RoundRobin { legs, schedule, .. } if schedule.is_empty() && teams.len() >= 2 => {
+                    *schedule = get_round_robin_schedule(&teams, *legs);
+                }
+                _ => return Err(()),
+            }
+            s.sync_schedule();
+            self.changed_tournaments.push(tournament_id);
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// Works out a round robin schedule using the circle method: one place in the circle stays put while the teams rotate
+/// around it, with teams opposite each other in the circle playing each other.
+/// The sides alternate as in a Berger table, so each team is team A in half of their matches (or as close as possible),
+/// and later legs swap the sides of the first leg's matches.
+fn get_round_robin_schedule(teams: &[TeamId], legs: u32) -> Vec<ScheduledMatch> {
+    // With an odd number of teams, the fixed place is a gap and whoever is opposite it sits out that round
+    let mut circle: Vec<Option<TeamId>> = teams.iter().map(|t| Some(*t)).collect();
+    if circle.len() % 2 == 1 {
+        circle.insert(0, None);
+    }
+    let n = circle.len();
+
+    let mut first_leg = vec![];
+    for round in 0..(n - 1) as u32 {
+        for i in 0..n / 2 {
+            if let (Some(a), Some(b)) = (circle[i], circle[n - 1 - i]) {
+                let swap = if i == 0 { round % 2 == 1 } else { i % 2 == 1 };
+                first_leg.push(if swap { (b, a, round) } else { (a, b, round) });
+            }
+        }
+        let last = circle.pop().unwrap_or(None);
+        circle.insert(1, last);
+    }
+
+    let mut result = vec![];
+    for leg in 0..legs {
+        for &(a, b, round) in &first_leg {
+            let (team_a, team_b) = if leg % 2 == 0 { (a, b) } else { (b, a) };
+            result.push(ScheduledMatch { team_a, team_b, round: round + leg * (n as u32 - 1), match_id: None, time: None });
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::model::schedule::get_round_robin_schedule;
+    use crate::model::tournament::{ScheduledMatch, StageKind, TeamId};
+
+    /// How many times each team plays in each round.
+    fn count_per_round(schedule: &[ScheduledMatch], team: TeamId) -> Vec<usize> {
+        let num_rounds = schedule.iter().map(|m| m.round + 1).max().unwrap_or(0);
+        (0..num_rounds).map(|r| schedule.iter().filter(|m| m.round == r && (m.team_a == team || m.team_b == team)).count()).collect()
+    }
+
+    #[test]
+    fn every_pair_plays_once_per_leg() {
+        for num_teams in 2..=9 {
+            let teams: Vec<TeamId> = (10..10 + num_teams).collect();
+            for legs in 1..=3 {
+                let schedule = get_round_robin_schedule(&teams, legs);
+                for (i, &a) in teams.iter().enumerate() {
+                    for &b in &teams[i + 1..] {
+                        assert_eq!(schedule.iter().filter(|m| [m.team_a, m.team_b] == [a, b] || [m.team_a, m.team_b] == [b, a]).count(), legs as usize);
+                    }
+                }
+                assert_eq!(schedule.len(), num_teams * (num_teams - 1) / 2 * legs as usize);
+                assert!(schedule.iter().all(|m| m.match_id.is_none() && m.time.is_none()));
+                // In order of round
+                assert!(schedule.windows(2).all(|w| w[0].round <= w[1].round));
+            }
+        }
+    }
+
+    #[test]
+    fn one_match_per_round() {
+        for num_teams in 2..=9 {
+            let teams: Vec<TeamId> = (0..num_teams).collect();
+            let schedule = get_round_robin_schedule(&teams, 2);
+            // With an odd number of teams, there's an extra round so that each team can sit one out
+            let rounds_per_leg = if num_teams % 2 == 0 { num_teams - 1 } else { num_teams };
+            for &t in &teams {
+                let counts = count_per_round(&schedule, t);
+                assert_eq!(counts.len(), rounds_per_leg * 2);
+                assert!(counts.iter().all(|c| *c <= 1));
+                let rests = counts.iter().filter(|c| **c == 0).count();
+                assert_eq!(rests, if num_teams % 2 == 0 { 0 } else { 2 });
+            }
+        }
+    }
+
+    #[test]
+    fn sides_are_balanced() {
+        for num_teams in 2..=9 {
+            let teams: Vec<TeamId> = (0..num_teams).collect();
+            let single = get_round_robin_schedule(&teams, 1);
+            let double = get_round_robin_schedule(&teams, 2);
+            for &t in &teams {
+                let team_a = single.iter().filter(|m| m.team_a == t).count() as i64;
+                let team_b = single.iter().filter(|m| m.team_b == t).count() as i64;
+                assert!((team_a - team_b).abs() <= 1, "{num_teams} teams: team {t} is team A {team_a} times and team B {team_b} times");
+                // The second leg swaps the sides of the first
+                assert_eq!(double.iter().filter(|m| m.team_a == t).count(), double.iter().filter(|m| m.team_b == t).count());
+            }
+            let (first_leg, second_leg) = double.split_at(double.len() / 2);
+            assert!(first_leg.iter().zip(second_leg).all(|(x, y)| (x.team_a, x.team_b) == (y.team_b, y.team_a)));
+        }
+    }
+
+    #[test]
+    fn generate() {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 1).unwrap();
+        let a = model.add_team(t, s, "A".to_string()).unwrap();
+        // Needs at least two teams
+        assert!(model.generate_round_robin_schedule(t, s).is_err());
+        let [b, c] = ["B", "C"].map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        // Matches which have already been played count towards the schedule
+        let m = model.add_match(t, s, c, a, 1, 0).unwrap();
+        model.generate_round_robin_schedule(t, s).unwrap();
+        match &model.get_stage(t, s).unwrap().kind {
+            StageKind::RoundRobin { schedule, .. } => {
+                assert_eq!(schedule.len(), 3);
+                let played: Vec<(TeamId, TeamId)> = schedule.iter().filter(|x| x.match_id == Some(m)).map(|x| (x.team_a, x.team_b)).collect();
+                assert!(played == vec![(a, c)] || played == vec![(c, a)]);
+                assert!(schedule.iter().filter(|x| x.match_id.is_none()).all(|x| [x.team_a, x.team_b].contains(&b)));
+            }
+            _ => panic!("Not a round robin"),
+        }
+        // There's already a schedule
+        assert!(model.generate_round_robin_schedule(t, s).is_err());
+    }
+}
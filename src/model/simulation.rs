@@ -0,0 +1,294 @@
+use std::rc::Rc;
+
+use indexmap::indexmap;
+
+use crate::model::Model;
+use crate::model::clinch::{ClinchStatus, ClinchTracker};
+use crate::model::tournament::{Match, Stage, StageId, StageKind, TeamId, TournamentId};
+
+/// The chances of each team finishing in each position of a round robin, worked out by playing out the remaining matches.
+/// See Stage::simulate_remaining_matches.
+#[derive(Debug)]
+pub struct Simulation {
+    /// The matches which are still to be played, as (team_a, team_b).
+    pub remaining_matches: Vec<(TeamId, TeamId)>,
+    /// How many different sets of results were used.
+    pub num_outcomes: usize,
+    /// True if every possible set of results was used, false if there were too many so a random sample was used instead.
+    pub exhaustive: bool,
+    /// One entry per team, in the order of the current standings.
+    pub teams: Vec<TeamOdds>,
+    /// What each team has already clinched or been eliminated from, in the order of the current standings.
+    /// These are worked out from the same sets of results as the chances, when every one of them is tried. See Stage::get_clinch_statuses.
+    pub clinch_statuses: Vec<ClinchStatus>,
+}
+
+#[derive(Debug)]
+pub struct TeamOdds {
+    pub team_id: TeamId,
+    /// The chance (from 0 to 1) of finishing in each position, with 1st place first.
+    pub position_chances: Vec<f64>,
+}
+
+impl TeamOdds {
+    /// The chance (from 0 to 1) of finishing in one of the top n positions.
+    pub fn get_top_chance(&self, n: usize) -> f64 {
+        self.position_chances.iter().take(n).sum()
+    }
+}
+
+impl Model {
+    /// Gets the simulation of the remaining matches of a round robin (see Stage::simulate_remaining_matches).
+    /// This is kept until the tournament changes, so that everything showing it (e.g. the chances and the clinch statuses
+    /// in the standings) doesn't have to work it out again. None if this isn't a round robin.
+    pub fn get_simulation(&self, tournament_id: TournamentId, stage_id: StageId) -> Option<Rc<Simulation>> {
+        if let Some(s) = self.simulations.borrow().get(&(tournament_id, stage_id)) {
+            return Some(s.clone());
+        }
+        let simulation = Rc::new(self.get_stage(tournament_id, stage_id)?.simulate_remaining_matches()?);
+        // If the tournament has been changed since the last process_updates, this would be thrown away then anyway
+        if !self.changed_tournaments.contains(&tournament_id) {
+            self.simulations.borrow_mut().insert((tournament_id, stage_id), simulation.clone());
+        }
+        Some(simulation)
+    }
+}
+
+impl Stage {
+    /// If there are more possible sets of results than this, then a random sample of them is used instead.
+    const MAX_EXHAUSTIVE_OUTCOMES: usize = 4096;
+    /// How many random sets of results to use when there are too many to try them all.
+    const NUM_SAMPLES: usize = 4096;
+
+    /// Gets the matches still to be played in a round robin: any scheduled matches which haven't been played yet,
+    /// plus enough to make up the number of legs between each pair of teams.
+    pub fn get_remaining_matches(&self) -> Vec<(TeamId, TeamId)> {
+        let legs = match self.kind {
+            StageKind::RoundRobin { legs, .. } => legs as usize,
+            _ => return vec![],
+        };
+        let mut result = vec![];
+        let team_ids: Vec<TeamId> = self.teams.keys().copied().collect();
+        for (i, &a) in team_ids.iter().enumerate() {
+            for &b in &team_ids[i + 1..] {
+                let scheduled = self.get_unplayed_scheduled_matches_between(a, b);
+                let unscheduled = legs.saturating_sub(self.get_matches_between(a, b).len()).saturating_sub(scheduled.len());
+                result.extend(scheduled.iter().map(|m| (m.team_a, m.team_b)));
+                result.extend(std::iter::repeat_n((a, b), unscheduled));
+            }
+        }
+        result
+    }
+
+    /// Works out how likely each team is to finish in each position of a round robin, by playing out every possible set of
+    /// results for the remaining matches (or a random sample, if there are too many) and applying the stage's tiebreakers.
+    /// Every possible score of a match is treated as equally likely. Teams left tied are shared evenly between the positions they tie for.
+    /// This also works out the clinch statuses (see get_clinch_statuses), so that the matches only need playing out once.
+    /// None if this isn't a round robin.
+    pub fn simulate_remaining_matches(&self) -> Option<Simulation> {
+        if !matches!(self.kind, StageKind::RoundRobin { .. }) {
+            return None;
+        }
+
+        let remaining_matches = self.get_remaining_matches();
+        let team_ids: Vec<TeamId> = self.get_standings().iter().map(|e| e.team_id).collect();
+        let mut counts = vec![vec![0.0; team_ids.len()]; team_ids.len()];
+        // Clinching can only be worked out exactly if every set of results is tried
+        let mut clinch_tracker = get_num_outcomes(remaining_matches.len(), self.series_format.get_possible_scores().len())
+            .map(|_| ClinchTracker::new(self, &team_ids));
+        let (num_outcomes, exhaustive) = self.play_out_matches(&remaining_matches, &mut |outcome| {
+            let placements = outcome.get_placements();
+            if let Some(tracker) = &mut clinch_tracker {
+                tracker.add_outcome(outcome, &placements);
+            }
+            for placement in &placements {
+                let share = 1.0 / placement.team_ids.len() as f64;
+                for t in &placement.team_ids {
+                    if let Some(team_index) = team_ids.iter().position(|x| x == t) {
+                        let first = placement.position - 1;
+                        for c in &mut counts[team_index][first..first + placement.team_ids.len()] {
+                            *c += share;
+                        }
+                    }
+                }
+            }
+        });
+
+        let teams = team_ids.iter().zip(counts).map(|(&team_id, c)| TeamOdds { team_id,
+            position_chances: c.iter().map(|x| x / num_outcomes as f64).collect() }).collect();
+        let clinch_statuses = match clinch_tracker {
+            Some(tracker) => tracker.get_statuses(&remaining_matches),
+            None => self.get_clinch_statuses_by_points(&remaining_matches),
+        };
+        Some(Simulation { remaining_matches, num_outcomes, exhaustive, teams, clinch_statuses })
+    }
+
+    /// Calls f for every possible set of results for the given matches (or a random sample, if there are too many).
+    /// Each time it's given a copy of this stage with those results added after the real ones. Only the things that affect
+    /// the standings are copied. Returns the number of sets of results, and whether that was all of them.
+    fn play_out_matches(&self, matches: &[(TeamId, TeamId)], f: &mut dyn FnMut(&Stage)) -> (usize, bool) {
+        let scores = self.series_format.get_possible_scores();
+        let total_outcomes = get_num_outcomes(matches.len(), scores.len());
+
+        let mut copy = Stage { id: self.id, tournament_id: self.tournament_id, name: String::new(), teams: self.teams.clone(), matches: self.matches.clone(),
+            kind: StageKind::RoundRobin { legs: 0, schedule: vec![], round_labels: indexmap![] }, tiebreakers: self.tiebreakers.clone(),
+            series_format: self.series_format, scoring_system: self.scoring_system, advancing_teams: self.advancing_teams };
+        let num_real_matches = copy.matches.len();
+        let first_new_id = self.matches.keys().max().map(|i| i + 1).unwrap_or(0);
+
+        let mut rng = Rng::new();
+        let num_outcomes = total_outcomes.unwrap_or(Stage::NUM_SAMPLES);
+        for outcome in 0..num_outcomes {
+            copy.matches.truncate(num_real_matches);
+            // When trying everything, the outcome number is read as a number in base scores.len(), with a digit for each match
+            let mut x = outcome;
+            for (i, &(team_a, team_b)) in matches.iter().enumerate() {
+                let choice = if total_outcomes.is_some() {
+                    let c = x % scores.len();
+                    x /= scores.len();
+                    c
+                } else {
+                    rng.next_below(scores.len())
+                };
+                let (team_a_score, team_b_score) = scores[choice];
+                let id = first_new_id + i;
+                copy.matches.insert(id, Match { id, team_a, team_b, team_a_score, team_b_score, is_tiebreaker: false });
+            }
+            f(&copy);
+        }
+        (num_outcomes, total_outcomes.is_some())
+    }
+}
+
+/// The number of possible sets of results for the given number of matches, or None if that's too many to try them all.
+fn get_num_outcomes(num_matches: usize, num_scores: usize) -> Option<usize> {
+    u32::try_from(num_matches).ok()
+        .and_then(|n| num_scores.checked_pow(n))
+        .filter(|n| *n <= Stage::MAX_EXHAUSTIVE_OUTCOMES)
+}
+
+/// Formats a chance from 0 to 1 as a percentage, making sure that anything which isn't certain doesn't get rounded to 0% or 100%.
+pub fn format_chance(chance: f64) -> String {
+    // Allow for rounding errors from adding up the shares of tied positions
+    const EPSILON: f64 = 1e-9;
+    match chance {
+        c if c < EPSILON => "-".to_string(),
+        c if c > 1.0 - EPSILON => "100%".to_string(),
+        c if c < 0.01 => "<1%".to_string(),
+        c if c > 0.99 => ">99%".to_string(),
+        c => format!("{:.0}%", c * 100.0),
+    }
+}
+
+/// A simple pseudo-random number generator (xorshift). This always starts from the same seed, so that the results of a simulation
+/// don't change each time it's shown.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new() -> Rng {
+        Rng { state: 0x2545F4914F6CDD1D }
+    }
+
+    fn next_below(&mut self, n: usize) -> usize {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::model::simulation::Simulation;
+    use crate::model::tournament::{SeriesFormat, StageId, TeamId, TournamentId};
+
+    /// A round robin between the given number of teams, with the given results (as indexes into the teams) already played.
+    fn new_round_robin(num_teams: usize, legs: u32, wins: &[(usize, usize)]) -> (Model, TournamentId, StageId, Vec<TeamId>) {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), legs).unwrap();
+        let teams: Vec<TeamId> = (0..num_teams).map(|i| model.add_team(t, s, format!("Team {i}")).unwrap()).collect();
+        for &(winner, loser) in wins {
+            model.add_match(t, s, teams[winner], teams[loser], 1, 0).unwrap();
+        }
+        (model, t, s, teams)
+    }
+
+    fn get_chances(simulation: &Simulation, team_id: TeamId) -> &[f64] {
+        &simulation.teams.iter().find(|x| x.team_id == team_id).unwrap().position_chances
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        assert!(actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-9), "{actual:?} != {expected:?}");
+    }
+
+    /// Checks that every team finishes somewhere, and that every position is filled by someone.
+    fn assert_chances_sum_to_one(simulation: &Simulation) {
+        let n = simulation.teams.len();
+        for team in &simulation.teams {
+            assert!((team.position_chances.iter().sum::<f64>() - 1.0).abs() < 1e-9, "{:?}", team.position_chances);
+        }
+        for position in 0..n {
+            assert!((simulation.teams.iter().map(|x| x.position_chances[position]).sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn chances_sum_to_one() {
+        // Every set of results is tried
+        let (model, t, s, _) = new_round_robin(4, 1, &[(0, 1), (2, 3)]);
+        let simulation = model.get_stage(t, s).unwrap().simulate_remaining_matches().unwrap();
+        assert_eq!(simulation.remaining_matches.len(), 4);
+        assert_eq!(simulation.num_outcomes, 16);
+        assert!(simulation.exhaustive);
+        assert_chances_sum_to_one(&simulation);
+
+        // With draws, there are more ties which are shared between positions
+        let (mut model, t, s, teams) = new_round_robin(4, 1, &[]);
+        model.set_stage_series_format(t, s, SeriesFormat::FixedGames(2)).unwrap();
+        model.add_match(t, s, teams[0], teams[1], 1, 1).unwrap();
+        let simulation = model.get_stage(t, s).unwrap().simulate_remaining_matches().unwrap();
+        assert_eq!(simulation.num_outcomes, 3usize.pow(5));
+        assert_chances_sum_to_one(&simulation);
+
+        // Too many sets of results to try them all, so a sample is used
+        let (model, t, s, _) = new_round_robin(5, 2, &[]);
+        let simulation = model.get_stage(t, s).unwrap().simulate_remaining_matches().unwrap();
+        assert_eq!(simulation.remaining_matches.len(), 20);
+        assert!(!simulation.exhaustive);
+        assert_chances_sum_to_one(&simulation);
+    }
+
+    #[test]
+    fn no_remaining_matches() {
+        let (model, t, s, teams) = new_round_robin(3, 1, &[(0, 1), (0, 2), (1, 2)]);
+        let simulation = model.get_stage(t, s).unwrap().simulate_remaining_matches().unwrap();
+        assert!(simulation.remaining_matches.is_empty());
+        assert_eq!(simulation.num_outcomes, 1);
+        assert!(simulation.exhaustive);
+        assert_eq!(simulation.teams.iter().map(|x| x.team_id).collect::<Vec<_>>(), teams);
+        assert_close(get_chances(&simulation, teams[0]), &[1.0, 0.0, 0.0]);
+        assert_close(get_chances(&simulation, teams[1]), &[0.0, 1.0, 0.0]);
+        assert_close(get_chances(&simulation, teams[2]), &[0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn worked_out_by_hand() {
+        // A has beaten B, leaving A v C and B v C. The four sets of results give:
+        // A and B beat C: A, B, C. A and C win: A, C, B. C and B win: a three-way tie. C wins both: C, A, B.
+        let (model, t, s, teams) = new_round_robin(3, 1, &[(0, 1)]);
+        let [a, b, c] = teams[..] else { panic!() };
+        let simulation = model.get_stage(t, s).unwrap().simulate_remaining_matches().unwrap();
+        assert_eq!(simulation.num_outcomes, 4);
+        // The tied teams get a third of each position they share
+        let third = 1.0 / 3.0;
+        assert_close(get_chances(&simulation, a), &[(2.0 + third) / 4.0, (third + 1.0) / 4.0, third / 4.0]);
+        assert_close(get_chances(&simulation, b), &[third / 4.0, (1.0 + third) / 4.0, (2.0 + third) / 4.0]);
+        assert_close(get_chances(&simulation, c), &[(third + 1.0) / 4.0, (1.0 + third) / 4.0, (1.0 + third) / 4.0]);
+        assert_eq!(simulation.teams[0].get_top_chance(2), simulation.teams[0].position_chances[0] + simulation.teams[0].position_chances[1]);
+    }
+}
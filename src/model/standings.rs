@@ -0,0 +1,413 @@
+use crate::model::tournament::{ScoringSystem, Stage, TeamId, Tiebreaker};
+
+/// One row of the standings for a stage.
+#[derive(Debug)]
+pub struct StandingsEntry {
+    pub team_id: TeamId,
+    /// 1 for first place etc. Teams which are tied share the same position.
+    pub position: usize,
+    /// If this team shares its position with other teams.
+    pub tied: bool,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    /// Points from the stage's scoring system. For a win/loss scoring system, this is the same as the number of wins.
+    pub points: i64,
+    pub games_played: usize,
+    pub score_differential: i64,
+    /// Why this team is placed below the previous entry in the standings. None for the first entry.
+    pub separation: Option<Separation>,
+}
+
+/// The reason that a team is placed below another team in the standings.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Separation {
+    /// The teams have different records (or points).
+    Record,
+    /// The teams have the same record, and were separated by the results of tiebreaker matches played between them.
+    TiebreakerMatch,
+    /// The teams have the same record, and were separated by this tiebreaker.
+    Tiebreaker(Tiebreaker),
+    /// None of the stage's tiebreakers could separate the teams, so a tiebreaker match is needed.
+    Unresolved,
+}
+
+/// A position in the standings, along with all the teams that share it.
+#[derive(Debug)]
+pub struct Placement {
+    pub position: usize,
+    pub team_ids: Vec<TeamId>,
+}
+
+impl StandingsEntry {
+    /// The proportion of games that this team has won, or None if they haven't played any yet.
+    pub fn get_win_rate(&self) -> Option<f64> {
+        if self.games_played == 0 {
+            None
+        } else {
+            Some(self.wins as f64 / self.games_played as f64)
+        }
+    }
+
+    /// Describes the position for display, e.g. "1st" or "T-3rd" if tied.
+    pub fn get_position_desc(&self) -> String {
+        format!("{}{}", if self.tied { "T-" } else { "" }, to_ordinal(self.position))
+    }
+}
+
+impl Separation {
+    /// Describes why a team with the same record as the team above them is placed lower, for showing in the standings.
+    /// None if the teams have different records, as that is self-explanatory.
+    pub fn get_tiebreak_desc(&self) -> Option<String> {
+        match self {
+            Separation::Record => None,
+            Separation::TiebreakerMatch => Some("by tiebreaker match".to_string()),
+            Separation::Tiebreaker(t) => Some(format!("by {}", t.get_name())),
+            Separation::Unresolved => Some("tied - needs tiebreaker match".to_string()),
+        }
+    }
+}
+
+impl Stage {
+    /// Gets all the teams in this stage, sorted by their record (or points, depending on the scoring system) with the best team first.
+    /// Teams with the same record are ordered using any tiebreaker matches played between them, and then the stage's tiebreakers.
+    pub fn get_standings(&self) -> Vec<StandingsEntry> {
+        let mut result = vec![];
+
+        let all_teams: Vec<TeamId> = self.teams.keys().copied().collect();
+        let groups = split_by_key(all_teams, |t| self.get_record_key(t));
+        for (i, group) in groups.into_iter().enumerate() {
+            self.break_ties(group, if i == 0 { None } else { Some(Separation::Record) }, &mut result);
+        }
+
+        // Teams which couldn't be separated share the position of the highest of them
+        for i in 0..result.len() {
+            if result[i].separation == Some(Separation::Unresolved) {
+                result[i].position = result[i - 1].position;
+                result[i].tied = true;
+                result[i - 1].tied = true;
+            } else {
+                result[i].position = i + 1;
+            }
+        }
+
+        result
+    }
+
+    /// Gets the positions in the standings, grouping together teams which are tied.
+    pub fn get_placements(&self) -> Vec<Placement> {
+        let mut result: Vec<Placement> = vec![];
+        for entry in self.get_standings() {
+            match result.last_mut() {
+                Some(p) if p.position == entry.position => p.team_ids.push(entry.team_id),
+                _ => result.push(Placement { position: entry.position, team_ids: vec![entry.team_id] }),
+            }
+        }
+        result
+    }
+
+    /// Orders a group of teams which have the same record, adding them to the end of the standings.
+    /// first_separation is how the first team in the group is separated from the team above it.
+    fn break_ties(&self, group: Vec<TeamId>, first_separation: Option<Separation>, result: &mut Vec<StandingsEntry>) {
+        if group.len() > 1 {
+            // Tiebreaker matches were played specifically to separate these teams, so take priority over everything else
+            let subgroups = split_by_key(group.clone(), |t| self.get_tiebreaker_match_value(t, &group));
+            if subgroups.len() > 1 {
+                for (i, subgroup) in subgroups.into_iter().enumerate() {
+                    self.break_ties(subgroup, if i == 0 { first_separation } else { Some(Separation::TiebreakerMatch) }, result);
+                }
+                return;
+            }
+
+            for tiebreaker in &self.tiebreakers {
+                let applies = match tiebreaker {
+                    Tiebreaker::HeadToHead => group.len() == 2,
+                    Tiebreaker::HeadToHeadMiniTable => group.len() > 2,
+                    Tiebreaker::ScoreDifferential | Tiebreaker::StrengthOfVictory | Tiebreaker::Buchholz => true,
+                };
+                if !applies {
+                    continue;
+                }
+
+                let subgroups = split_by_key(group.clone(), |t| self.get_tiebreaker_value(*tiebreaker, t, &group));
+                if subgroups.len() > 1 {
+                    // Any teams still tied go through all the tiebreakers again from the start,
+                    // e.g. a three-way tie might be reduced to a two-way tie which is then decided by head-to-head
+                    for (i, subgroup) in subgroups.into_iter().enumerate() {
+                        self.break_ties(subgroup, if i == 0 { first_separation } else { Some(Separation::Tiebreaker(*tiebreaker)) }, result);
+                    }
+                    return;
+                }
+            }
+        }
+
+        // Either there's only one team, or nothing could separate them
+        for (i, team_id) in group.into_iter().enumerate() {
+            let (wins, losses) = self.get_win_loss(team_id);
+            result.push(StandingsEntry { team_id, position: 0, tied: false, wins, draws: self.get_draws(team_id), losses, points: self.get_points(team_id),
+                games_played: self.get_games_played(team_id), score_differential: self.get_score_differential(team_id),
+                separation: if i == 0 { first_separation } else { Some(Separation::Unresolved) } });
+        }
+    }
+
+    /// Teams with the same key have the same record, and so need tiebreaking. Higher values are better.
+    fn get_record_key(&self, team_id: TeamId) -> (i64, i64) {
+        match self.scoring_system {
+            ScoringSystem::WinLoss => {
+                let (w, l) = self.get_win_loss(team_id);
+                (w as i64, -(l as i64))
+            }
+            // Only points matter, e.g. in football a team with more draws can be level with a team with more wins
+            ScoringSystem::Points { .. } | ScoringSystem::GamePoints { .. } => (self.get_points(team_id), 0),
+        }
+    }
+
+    /// Higher values are better.
+    fn get_tiebreaker_value(&self, tiebreaker: Tiebreaker, team_id: TeamId, tied_teams: &[TeamId]) -> i64 {
+        match tiebreaker {
+            Tiebreaker::HeadToHead | Tiebreaker::HeadToHeadMiniTable => {
+                // Only count matches between the tied teams
+                let mut result = 0;
+                for m in self.get_regular_matches().filter(|m| tied_teams.contains(&m.team_a) && tied_teams.contains(&m.team_b)) {
+                    if m.get_winner() == Some(team_id) {
+                        result += 1;
+                    } else if m.get_loser() == Some(team_id) {
+                        result -= 1;
+                    }
+                }
+                result
+            }
+            Tiebreaker::ScoreDifferential => self.get_score_differential(team_id),
+            Tiebreaker::StrengthOfVictory => {
+                self.get_regular_matches().filter(|m| m.get_winner() == Some(team_id))
+                    .filter_map(|m| m.get_loser())
+                    .map(|beaten_team_id| self.get_win_loss(beaten_team_id).0 as i64)
+                    .sum()
+            }
+            Tiebreaker::Buchholz => self.get_buchholz(team_id),
+        }
+    }
+
+    /// The number of tiebreaker matches won minus the number lost, only counting those between the tied teams.
+    fn get_tiebreaker_match_value(&self, team_id: TeamId, tied_teams: &[TeamId]) -> i64 {
+        let mut result = 0;
+        for m in self.get_tiebreaker_matches().filter(|m| tied_teams.contains(&m.team_a) && tied_teams.contains(&m.team_b)) {
+            if m.get_winner() == Some(team_id) {
+                result += 1;
+            } else if m.get_loser() == Some(team_id) {
+                result -= 1;
+            }
+        }
+        result
+    }
+}
+
+/// Splits the given teams into groups which have the same key, ordered with the highest key first.
+/// The order of teams within each group is preserved.
+fn split_by_key<K: Ord, F: FnMut(TeamId) -> K>(teams: Vec<TeamId>, mut f: F) -> Vec<Vec<TeamId>> {
+    let mut keyed: Vec<(K, TeamId)> = teams.into_iter().map(|t| (f(t), t)).collect();
+    // Stable sort, so teams with the same key stay in the same order
+    keyed.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut result: Vec<Vec<TeamId>> = vec![];
+    let mut prev_key = None;
+    for (k, t) in keyed {
+        match (&prev_key, result.last_mut()) {
+            (Some(pk), Some(last)) if *pk == k => last.push(t),
+            _ => result.push(vec![t]),
+        }
+        prev_key = Some(k);
+    }
+    result
+}
+
+/// Formats a number as an ordinal, e.g. 1st, 2nd, 3rd, 4th.
+pub fn to_ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::model::standings::{Separation, to_ordinal};
+    use crate::model::tournament::{ScoringSystem, SeriesFormat, StageId, TeamId, Tiebreaker, TournamentId};
+
+    /// A round robin with only the given tiebreakers. The teams are added in the order given, which is the order
+    /// they stay in if nothing separates them.
+    fn new_stage<const N: usize>(names: [&str; N], tiebreakers: Vec<Tiebreaker>) -> (Model, TournamentId, StageId, [TeamId; N]) {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 1).unwrap();
+        model.set_stage_tiebreakers(t, s, tiebreakers).unwrap();
+        let teams = names.map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        (model, t, s, teams)
+    }
+
+    /// Adds a 1-0 win for the first team of each pair.
+    fn add_wins(model: &mut Model, t: TournamentId, s: StageId, wins: &[(TeamId, TeamId)]) {
+        for &(winner, loser) in wins {
+            model.add_match(t, s, winner, loser, 1, 0).unwrap();
+        }
+    }
+
+    /// The teams in standings order, with how each is separated from the team above.
+    fn get_order(model: &Model, t: TournamentId, s: StageId) -> Vec<(TeamId, Option<Separation>)> {
+        model.get_stage(t, s).unwrap().get_standings().iter().map(|e| (e.team_id, e.separation)).collect()
+    }
+
+    #[test]
+    fn head_to_head() {
+        let (mut model, t, s, [b, a, c, d]) = new_stage(["B", "A", "C", "D"], vec![Tiebreaker::HeadToHead]);
+        // A and B are both 1-1, and A beat B
+        add_wins(&mut model, t, s, &[(a, b), (c, a), (b, d)]);
+        assert_eq!(get_order(&model, t, s), vec![(c, None), (a, Some(Separation::Record)),
+            (b, Some(Separation::Tiebreaker(Tiebreaker::HeadToHead))), (d, Some(Separation::Record))]);
+    }
+
+    #[test]
+    fn mini_table() {
+        let (mut model, t, s, [c, b, a, d, e]) = new_stage(["C", "B", "A", "D", "E"], vec![Tiebreaker::HeadToHead, Tiebreaker::HeadToHeadMiniTable]);
+        // A, B and C are all 2-2. Between themselves, A is 2-0, B is 1-1 and C is 0-2.
+        add_wins(&mut model, t, s, &[(a, b), (a, c), (b, c), (d, a), (e, a), (b, d), (e, b), (c, d), (c, e)]);
+        let mini_table = Some(Separation::Tiebreaker(Tiebreaker::HeadToHeadMiniTable));
+        assert_eq!(get_order(&model, t, s), vec![(e, None), (a, Some(Separation::Record)), (b, mini_table), (c, mini_table), (d, Some(Separation::Record))]);
+    }
+
+    #[test]
+    fn cyclic_three_way_tie() {
+        let (mut model, t, s, [a, b, c]) = new_stage(["A", "B", "C"], Tiebreaker::defaults());
+        // Every tiebreaker comes out the same for all of them
+        add_wins(&mut model, t, s, &[(a, b), (b, c), (c, a)]);
+        assert_eq!(get_order(&model, t, s), vec![(a, None), (b, Some(Separation::Unresolved)), (c, Some(Separation::Unresolved))]);
+
+        let standings = model.get_stage(t, s).unwrap().get_standings();
+        assert!(standings.iter().all(|e| e.position == 1 && e.tied));
+        assert_eq!(standings[0].get_position_desc(), "T-1st");
+        assert_eq!(standings[1].separation.unwrap().get_tiebreak_desc().as_deref(), Some("tied - needs tiebreaker match"));
+    }
+
+    #[test]
+    fn score_differential() {
+        let (mut model, t, s, [a, b, c, d]) = new_stage(["A", "B", "C", "D"], vec![Tiebreaker::ScoreDifferential]);
+        model.set_stage_series_format(t, s, SeriesFormat::BestOf(3)).unwrap();
+        // Everyone is 1-1. A is +1, B is -1, and C and D are both 0 so stay tied.
+        for (team_a, team_b, team_a_score, team_b_score) in [(a, c, 2, 0), (d, a, 2, 1), (b, d, 2, 1), (c, b, 2, 0)] {
+            model.add_match(t, s, team_a, team_b, team_a_score, team_b_score).unwrap();
+        }
+        let score_differential = Some(Separation::Tiebreaker(Tiebreaker::ScoreDifferential));
+        assert_eq!(get_order(&model, t, s), vec![(a, None), (c, score_differential), (d, Some(Separation::Unresolved)), (b, score_differential)]);
+        let positions: Vec<String> = model.get_stage(t, s).unwrap().get_standings().iter().map(|e| e.get_position_desc()).collect();
+        assert_eq!(positions, vec!["1st", "T-2nd", "T-2nd", "4th"]);
+    }
+
+    #[test]
+    fn strength_of_victory() {
+        let (mut model, t, s, [b, a, strong, weak]) = new_stage(["B", "A", "Strong", "Weak"], vec![Tiebreaker::StrengthOfVictory]);
+        // A and B are both 1-1 and haven't played each other, but A's win was against a team with more wins
+        add_wins(&mut model, t, s, &[(a, strong), (weak, a), (b, weak), (strong, b), (strong, weak)]);
+        assert_eq!(get_order(&model, t, s), vec![(strong, None), (a, Some(Separation::Record)),
+            (b, Some(Separation::Tiebreaker(Tiebreaker::StrengthOfVictory))), (weak, Some(Separation::Record))]);
+    }
+
+    #[test]
+    fn buchholz() {
+        let (mut model, t, s, [b, a, c, d, e]) = new_stage(["B", "A", "C", "D", "E"], vec![Tiebreaker::Buchholz]);
+        // A and B are both 1-0, but A's opponent has won a match and B's hasn't. Likewise C and E are both 1-1.
+        add_wins(&mut model, t, s, &[(a, c), (b, d), (c, e), (e, d)]);
+        let buchholz = Some(Separation::Tiebreaker(Tiebreaker::Buchholz));
+        assert_eq!(get_order(&model, t, s), vec![(a, None), (b, buchholz), (c, Some(Separation::Record)), (e, buchholz), (d, Some(Separation::Record))]);
+    }
+
+    #[test]
+    fn tiebreakers_go_back_to_the_start() {
+        let (mut model, t, s, [c, b, a, d]) = new_stage(["C", "B", "A", "D"], vec![Tiebreaker::HeadToHead, Tiebreaker::ScoreDifferential]);
+        model.set_stage_series_format(t, s, SeriesFormat::BestOf(3)).unwrap();
+        // A, B and C are all 2-1, and beat each other in a cycle. A three-way tie can't use head-to-head, so score differential
+        // puts C last, as they only beat D 2-1. A and B are still level, so head-to-head is then used between just them.
+        for (team_a, team_b, team_a_score, team_b_score) in [(a, b, 2, 1), (b, c, 2, 1), (c, a, 2, 1), (a, d, 2, 0), (b, d, 2, 0), (c, d, 2, 1)] {
+            model.add_match(t, s, team_a, team_b, team_a_score, team_b_score).unwrap();
+        }
+        assert_eq!(get_order(&model, t, s), vec![(a, None), (b, Some(Separation::Tiebreaker(Tiebreaker::HeadToHead))),
+            (c, Some(Separation::Tiebreaker(Tiebreaker::ScoreDifferential))), (d, Some(Separation::Record))]);
+    }
+    #[test]
+    fn shared_placements() {
+        let (mut model, t, s, [a, b, c, d]) = new_stage(["A", "B", "C", "D"], Tiebreaker::defaults());
+        // A, B and C can't be separated, so all share 1st, and the next team is 4th
+        add_wins(&mut model, t, s, &[(a, b), (b, c), (c, a), (a, d), (b, d), (c, d)]);
+        let stage = model.get_stage(t, s).unwrap();
+        let placements: Vec<(usize, Vec<TeamId>)> = stage.get_placements().into_iter().map(|p| (p.position, p.team_ids)).collect();
+        assert_eq!(placements, vec![(1, vec![a, b, c]), (4, vec![d])]);
+        let positions: Vec<String> = stage.get_standings().iter().map(|e| e.get_position_desc()).collect();
+        assert_eq!(positions, vec!["T-1st", "T-1st", "T-1st", "4th"]);
+    }
+
+    #[test]
+    fn ordinals() {
+        let ordinals: Vec<String> = [1, 2, 3, 4, 10, 11, 12, 13, 14, 21, 22, 23, 101, 111, 112, 113].into_iter().map(to_ordinal).collect();
+        assert_eq!(ordinals, vec!["1st", "2nd", "3rd", "4th", "10th", "11th", "12th", "13th", "14th", "21st", "22nd", "23rd", "101st", "111th", "112th", "113th"]);
+    }
+    #[test]
+    fn double_round_robin() {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 2).unwrap();
+        let [a, b, c] = ["A", "B", "C"].map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        // A splits their legs with both B and C, and B beats C twice
+        let first_leg = model.add_match(t, s, a, b, 1, 0).unwrap();
+        let second_leg = model.add_match(t, s, a, b, 0, 1).unwrap();
+        add_wins(&mut model, t, s, &[(a, c), (c, a), (b, c), (b, c)]);
+
+        let stage = model.get_stage(t, s).unwrap();
+        assert_eq!(stage.get_matches_between(b, a).iter().map(|m| m.id).collect::<Vec<_>>(), vec![first_leg, second_leg]);
+        let records: Vec<(TeamId, usize, usize, usize)> = stage.get_standings().iter().map(|e| (e.team_id, e.wins, e.losses, e.games_played)).collect();
+        assert_eq!(records, vec![(b, 3, 1, 4), (a, 2, 2, 4), (c, 1, 3, 4)]);
+    }
+    #[test]
+    fn tiebreaker_matches() {
+        let (mut model, t, s, [a, b, c]) = new_stage(["A", "B", "C"], Tiebreaker::defaults());
+        add_wins(&mut model, t, s, &[(a, b), (b, c), (c, a)]);
+        // C wins a tiebreaker match against each of the others, which puts them top. A and B are still tied with each other,
+        // so their regular match is used to separate them.
+        model.add_tiebreaker_match(t, s, c, a, 1, 0).unwrap();
+        model.add_tiebreaker_match(t, s, b, c, 0, 1).unwrap();
+        assert_eq!(get_order(&model, t, s), vec![(c, None), (a, Some(Separation::TiebreakerMatch)),
+            (b, Some(Separation::Tiebreaker(Tiebreaker::HeadToHead)))]);
+
+        // They don't count towards the records
+        let records: Vec<(usize, usize, usize, i64)> = model.get_stage(t, s).unwrap().get_standings().iter()
+            .map(|e| (e.wins, e.losses, e.games_played, e.score_differential)).collect();
+        assert_eq!(records, vec![(1, 1, 2, 0); 3]);
+        assert_eq!(model.get_stage(t, s).unwrap().get_standings()[1].get_position_desc(), "2nd");
+    }
+    #[test]
+    fn draws() {
+        let (mut model, t, s, [a, b, c, d]) = new_stage(["A", "B", "C", "D"], Tiebreaker::defaults());
+        model.set_stage_series_format(t, s, SeriesFormat::FixedGames(2)).unwrap();
+        model.set_stage_scoring_system(t, s, ScoringSystem::THREE_ONE_ZERO).unwrap();
+        let draw = model.add_match(t, s, a, b, 1, 1).unwrap();
+        for (team_a, team_b, team_a_score, team_b_score) in [(a, c, 1, 1), (a, d, 1, 1), (b, c, 2, 0), (d, b, 2, 0), (c, d, 1, 1)] {
+            model.add_match(t, s, team_a, team_b, team_a_score, team_b_score).unwrap();
+        }
+
+        let stage = model.get_stage(t, s).unwrap();
+        let m = &stage.matches[&draw];
+        assert!(m.is_draw() && m.get_winner().is_none() && m.get_loser().is_none());
+        assert!(stage.has_draws());
+        // W - D - L, and points
+        let records: Vec<(TeamId, usize, usize, usize, i64)> = stage.get_standings().iter().map(|e| (e.team_id, e.wins, e.draws, e.losses, e.points)).collect();
+        assert_eq!(records, vec![(d, 1, 2, 0, 5), (b, 1, 1, 1, 4), (a, 0, 3, 0, 3), (c, 0, 2, 1, 2)]);
+
+        // A's draws are worth nothing without points, so they are level with C on wins but ahead on losses
+        model.set_stage_scoring_system(t, s, ScoringSystem::WinLoss).unwrap();
+        let records: Vec<(TeamId, i64)> = model.get_stage(t, s).unwrap().get_standings().iter().map(|e| (e.team_id, e.points)).collect();
+        assert_eq!(records, vec![(d, 1), (b, 1), (a, 0), (c, 0)]);
+    }
+}
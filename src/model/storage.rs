@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+use web_sys::Storage;
+use web_sys::window;
+
+/// Somewhere that the Model can be persisted to and loaded from.
+/// This is a simple key-value store (modelled on the browser's localStorage), so that the Model can
+/// store its version separately from the data itself.
+/// Having this as a trait means that the Model doesn't need to know whether it is running
+/// in a browser or natively (e.g. in a command-line tool).
+pub trait StorageBackend: Debug {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String>;
+    fn set_item(&mut self, key: &str, value: &str) -> Result<(), String>;
+}
+
+/// Stores data in the browser's localStorage. Only works when running in a browser!
+#[derive(Debug)]
+pub struct LocalStorageBackend {
+    storage: Storage,
+}
+
+impl LocalStorageBackend {
+    pub fn new() -> LocalStorageBackend {
+        let storage = window().expect("Missing window").local_storage().expect("Error getting localStorage").expect("Missing localStorage");
+        LocalStorageBackend { storage }
+    }
+}
+
+impl Default for LocalStorageBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StorageBackend for LocalStorageBackend {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String> {
+        self.storage.get_item(key).map_err(|e| format!("{e:?}"))
+    }
+
+    fn set_item(&mut self, key: &str, value: &str) -> Result<(), String> {
+        self.storage.set_item(key, value).map_err(|e| format!("{e:?}"))
+    }
+}
+
+/// Stores data in memory only, so nothing is persisted once this is dropped.
+#[derive(Debug, Default)]
+pub struct InMemoryStorageBackend {
+    items: HashMap<String, String>,
+}
+
+impl InMemoryStorageBackend {
+    pub fn new() -> InMemoryStorageBackend {
+        InMemoryStorageBackend { items: HashMap::new() }
+    }
+}
+
+impl StorageBackend for InMemoryStorageBackend {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String> {
+        Ok(self.items.get(key).cloned())
+    }
+
+    fn set_item(&mut self, key: &str, value: &str) -> Result<(), String> {
+        self.items.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+}
+
+/// Stores data natively on disk, with one file per key inside the given directory.
+#[derive(Debug)]
+pub struct FileStorageBackend {
+    dir: PathBuf,
+}
+
+impl FileStorageBackend {
+    pub fn new(dir: PathBuf) -> FileStorageBackend {
+        FileStorageBackend { dir }
+    }
+}
+
+impl StorageBackend for FileStorageBackend {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String> {
+        match std::fs::read_to_string(self.dir.join(key)) {
+            Ok(s) => Ok(Some(s)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {key}: {e}")),
+        }
+    }
+
+    fn set_item(&mut self, key: &str, value: &str) -> Result<(), String> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create {}: {e}", self.dir.display()))?;
+        std::fs::write(self.dir.join(key), value).map_err(|e| format!("Failed to write {key}: {e}"))
+    }
+}
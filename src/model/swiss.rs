@@ -0,0 +1,181 @@
+use crate::model::tournament::{Stage, StageKind, TeamId};
+
+/// Where a team is in a Swiss stage.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SwissStatus {
+    /// The team still has more rounds to play.
+    Playing,
+    /// The team has reached the number of wins needed to advance.
+    Advanced,
+    /// The team has reached the number of losses that eliminates them.
+    Eliminated,
+}
+
+impl SwissStatus {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            SwissStatus::Playing => "playing",
+            SwissStatus::Advanced => "advanced",
+            SwissStatus::Eliminated => "eliminated",
+        }
+    }
+}
+
+impl Stage {
+    /// Whether the given team has advanced or been eliminated yet. Always Playing for stages which aren't Swiss.
+    pub fn get_swiss_status(&self, team_id: TeamId) -> SwissStatus {
+        match self.kind {
+            StageKind::Swiss { advance_wins, eliminate_losses, .. } => {
+                let (w, l) = self.get_win_loss(team_id);
+                if w >= advance_wins as usize {
+                    SwissStatus::Advanced
+                } else if l >= eliminate_losses as usize {
+                    SwissStatus::Eliminated
+                } else {
+                    SwissStatus::Playing
+                }
+            }
+            _ => SwissStatus::Playing,
+        }
+    }
+
+    /// The total points of every opponent that the given team has played (not counting tiebreaker matches).
+    pub fn get_buchholz(&self, team_id: TeamId) -> i64 {
+        self.get_regular_matches().filter(|m| m.contains(team_id))
+            .map(|m| self.get_points(if m.team_a == team_id { m.team_b } else { m.team_a }))
+            .sum()
+    }
+
+    /// Works out the pairings for the next round of a Swiss stage, between all the teams which are still playing.
+    /// Teams play someone with the same record where possible, with the highest placed team playing the lowest placed one.
+    /// Rematches are avoided unless there's no other way to pair everyone up.
+    /// None if this isn't a Swiss stage, or there isn't an even number of teams still playing.
+    pub fn get_next_swiss_pairings(&self) -> Option<Vec<(TeamId, TeamId)>> {
+        if !matches!(self.kind, StageKind::Swiss { .. }) {
+            return None;
+        }
+
+        // The standings order takes care of ranking teams with the same record (e.g. by Buchholz)
+        let teams: Vec<(TeamId, (usize, usize))> = self.get_standings().into_iter()
+            .filter(|e| self.get_swiss_status(e.team_id) == SwissStatus::Playing)
+            .map(|e| (e.team_id, (e.wins, e.losses)))
+            .collect();
+        if !teams.len().is_multiple_of(2) {
+            return None;
+        }
+
+        pair_teams(&teams, &|a, b| self.get_matches_between(a, b).is_empty())
+            .or_else(|| pair_teams(&teams, &|_, _| true))
+    }
+}
+
+/// Pairs up all the given teams (which are in standings order, with their win/loss record), only using pairs that are allowed.
+/// Each team is paired with the lowest placed team with the same record if possible, otherwise the closest team below them.
+/// This backtracks if a choice of opponent makes it impossible to pair up the remaining teams.
+fn pair_teams(teams: &[(TeamId, (usize, usize))], allowed: &dyn Fn(TeamId, TeamId) -> bool) -> Option<Vec<(TeamId, TeamId)>> {
+    let ((first, record), rest) = match teams.split_first() {
+        Some(x) => x,
+        None => return Some(vec![]),
+    };
+
+    let same_record = (0..rest.len()).rev().filter(|&i| rest[i].1 == *record);
+    let different_record = (0..rest.len()).filter(|&i| rest[i].1 != *record);
+    for i in same_record.chain(different_record) {
+        let opponent = rest[i].0;
+        if !allowed(*first, opponent) {
+            continue;
+        }
+        let remaining: Vec<(TeamId, (usize, usize))> = rest.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, t)| *t).collect();
+        if let Some(mut result) = pair_teams(&remaining, allowed) {
+            result.insert(0, (*first, opponent));
+            return Some(result);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::model::swiss::pair_teams;
+    use crate::model::tournament::{StageId, StageKind, TeamId, TournamentId};
+
+    #[test]
+    fn pairs_within_records() {
+        // The top team plays the lowest placed team with the same record
+        let teams = [(1, (0, 0)), (2, (0, 0)), (3, (0, 0)), (4, (0, 0))];
+        assert_eq!(pair_teams(&teams, &|_, _| true), Some(vec![(1, 4), (2, 3)]));
+        let teams = [(1, (1, 0)), (2, (1, 0)), (3, (0, 1)), (4, (0, 1))];
+        assert_eq!(pair_teams(&teams, &|_, _| true), Some(vec![(1, 2), (3, 4)]));
+        // With an odd number of teams with a record, one of them has to play the closest team below
+        let teams = [(1, (2, 0)), (2, (1, 1)), (3, (1, 1)), (4, (1, 1)), (5, (0, 2)), (6, (0, 2))];
+        assert_eq!(pair_teams(&teams, &|_, _| true), Some(vec![(1, 2), (3, 4), (5, 6)]));
+    }
+
+    #[test]
+    fn avoids_disallowed_pairs() {
+        let not = |x: (TeamId, TeamId)| move |a, b| (a, b) != x && (b, a) != x;
+        // 1 and 2 can't play, so both play someone with a different record instead
+        let teams = [(1, (1, 0)), (2, (1, 0)), (3, (0, 1)), (4, (0, 1))];
+        assert_eq!(pair_teams(&teams, &not((1, 2))), Some(vec![(1, 3), (2, 4)]));
+        // 1 v 4 is allowed, but would leave 2 v 3 which isn't, so 1 has to play someone else
+        let teams = [(1, (0, 0)), (2, (0, 0)), (3, (0, 0)), (4, (0, 0))];
+        assert_eq!(pair_teams(&teams, &not((2, 3))), Some(vec![(1, 3), (2, 4)]));
+        assert_eq!(pair_teams(&teams, &|_, _| false), None);
+    }
+
+    fn new_swiss(num_teams: usize, rounds: u32, advance_wins: u32, eliminate_losses: u32) -> (Model, TournamentId, StageId) {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_swiss(t, "Swiss".to_string(), rounds, advance_wins, eliminate_losses).unwrap();
+        for i in 0..num_teams {
+            model.add_team(t, s, format!("Team {i}")).unwrap();
+        }
+        (model, t, s)
+    }
+
+    /// Generates the next round, with team A winning every pairing.
+    fn play_round(model: &mut Model, t: TournamentId, s: StageId) -> Vec<(TeamId, TeamId)> {
+        model.generate_swiss_round(t, s).unwrap();
+        let (round_idx, round) = match &model.get_stage(t, s).unwrap().kind {
+            StageKind::Swiss { pairings, .. } => (pairings.len() - 1, pairings.last().unwrap().iter().map(|p| (p.team_a, p.team_b)).collect::<Vec<_>>()),
+            _ => panic!("Not a Swiss stage"),
+        };
+        for pairing in 0..round.len() {
+            model.set_swiss_pairing_score(t, s, round_idx, pairing, 1, 0).unwrap();
+        }
+        round
+    }
+
+    #[test]
+    fn rematches_only_when_needed() {
+        // Nobody advances or is eliminated, so everyone keeps playing
+        let (mut model, t, s) = new_swiss(4, 4, 4, 4);
+        let mut played: Vec<(TeamId, TeamId)> = vec![];
+        for _ in 0..3 {
+            for (a, b) in play_round(&mut model, t, s) {
+                assert!(!played.contains(&(a, b)) && !played.contains(&(b, a)));
+                played.push((a, b));
+            }
+        }
+        // Everyone has played everyone, so the last round has to have rematches
+        let last_round = play_round(&mut model, t, s);
+        assert_eq!(last_round.len(), 2);
+        assert!(last_round.iter().all(|&(a, b)| played.contains(&(a, b)) || played.contains(&(b, a))));
+        assert_eq!(model.generate_swiss_round(t, s), Err("All 4 rounds have already been played.".to_string()));
+    }
+
+    #[test]
+    fn odd_number_of_teams() {
+        let (mut model, t, s) = new_swiss(5, 3, 2, 2);
+        assert_eq!(model.generate_swiss_round(t, s),
+            Err("There are 5 teams, but byes aren't supported so there needs to be an even number. Add or remove a team.".to_string()));
+
+        // Teams advance after a single win, so half of them go straight through and leave an odd number
+        let (mut model, t, s) = new_swiss(6, 3, 1, 2);
+        play_round(&mut model, t, s);
+        assert_eq!(model.generate_swiss_round(t, s),
+            Err("There are 3 teams still playing, which can't all be paired up as byes aren't supported.".to_string()));
+        assert_eq!(model.get_stage(t, s).unwrap().get_next_swiss_pairings(), None);
+    }
+}
@@ -7,14 +7,14 @@ use serde::Serialize;
 // The Model handles all that. Nobody outside the Model can get a mutable reference
 // to the data here so can't modify it incorrectly.
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Tournament {
     pub id: TournamentId,
     pub name: String,
     pub stages: IndexMap<StageId, Stage>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Stage  {
     pub id: StageId,
     pub tournament_id: TournamentId,
@@ -24,16 +24,47 @@ pub struct Stage  {
     pub matches: IndexMap<MatchId, Match>,
     #[serde(default = "default_stage_kind_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
     pub kind: StageKind,
+    /// How to order teams which have the same win/loss record, in order of priority.
+    #[serde(default = "Tiebreaker::defaults")] // This field was added, so give it a default value so that we can deserialize old data
+    pub tiebreakers: Vec<Tiebreaker>,
+    /// How many games are played in each match, which determines the scores that a match can have.
+    #[serde(default = "default_series_format_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+    pub series_format: SeriesFormat,
+    /// How the teams are ranked in the standings.
+    #[serde(default = "default_scoring_system_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+    pub scoring_system: ScoringSystem,
+    /// How many teams at the top of the standings go through to the next stage (e.g. the playoffs). 0 if there is no cut.
+    #[serde(default = "default_advancing_teams_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+    pub advancing_teams: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum StageKind {
     RoundRobin {
-
+        /// The number of times that each pair of teams plays each other, e.g. 2 for a double round robin.
+        #[serde(default = "default_legs_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+        legs: u32,
+        /// The matches that are going to be played, in order. Empty if no schedule has been made.
+        #[serde(default = "default_schedule_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+        schedule: Vec<ScheduledMatch>,
+        /// Names for the rounds of the schedule (e.g. "Week 1" or a date), keyed by round. Rounds without a name are just numbered.
+        #[serde(default = "default_round_labels_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+        round_labels: IndexMap<u32, String>,
     },
     Bracket {
         fixtures: IndexMap<FixtureId, Fixture>,
-    }
+    },
+    /// Each round, teams are paired against opponents with the same (or a similar) record whom they haven't played yet.
+    Swiss {
+        /// The most rounds that will be played.
+        rounds: u32,
+        /// Teams advance out of the stage (and stop playing) once they have this many wins, e.g. 3 for the "3-x" teams.
+        advance_wins: u32,
+        /// Teams are eliminated (and stop playing) once they have this many losses, e.g. 3 for the "x-3" teams.
+        eliminate_losses: u32,
+        /// The pairings for each round generated so far, in order.
+        pairings: Vec<Vec<SwissPairing>>,
+    },
 }
 
 pub type TournamentId = usize;
@@ -42,25 +73,28 @@ pub type TeamId = usize;
 pub type MatchId = usize;
 pub type FixtureId = usize;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Team {
     pub id: TeamId,
     pub name: String,
 }
 
 /// A match is something that we already have the results for. See also Fixture.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Match {
     pub id: MatchId,
     pub team_a: TeamId,
     pub team_b: TeamId,
     pub team_a_score: u32,
     pub team_b_score: u32,
+    /// Tiebreaker matches are played to separate teams which are tied in the standings, e.g. after a round robin.
+    /// They don't count towards the teams' records, only towards ordering the teams that they were played between.
+    pub is_tiebreaker: bool,
 }
 
 /// A fixture is a match that might not yet have been played, used to describe an elimination bracket.
 /// See also Match.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Fixture {
     pub id: FixtureId,
     /// The position of this fixture on the bracket view.
@@ -71,9 +105,21 @@ pub struct Fixture {
     /// that is recorded here. E.g. in an elimination bracket the winner will advance to the next fixture.
     pub team_a: FixtureTeam,
     pub team_b: FixtureTeam,
+    /// Which of the winner and loser of this fixture leave the bracket here, and whether they advance or are eliminated.
+    /// This can't always be worked out from the links between fixtures (e.g. the winner of a third place match doesn't advance,
+    /// and the winner of a grand final with a bracket reset only advances if they came from the upper bracket), so is only
+    /// recorded by the generators which know it, e.g. for a GSL group.
+    #[serde(default = "default_exits_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+    pub exits: Vec<(Outcome, BracketExit)>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// What happens to a team which leaves a bracket. See Fixture::exits.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum BracketExit {
+    Advance, Eliminate,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
 pub enum FixtureTeam {
     /// The team playing in this fixture is fixed, i.e. pre-determined and not based on the result of another fixture.
     Fixed(TeamId),
@@ -84,11 +130,85 @@ pub enum FixtureTeam {
     }
 }
 
+/// A rule for deciding the order of teams in the standings which have the same win/loss record.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum Tiebreaker {
+    /// The result(s) between two tied teams. Only used for two-way ties.
+    HeadToHead,
+    /// A table of only the matches between the tied teams. Only used for ties between three or more teams.
+    HeadToHeadMiniTable,
+    /// The total of the team's scores minus their opponents' scores, e.g. game differential for a best-of-three series.
+    ScoreDifferential,
+    /// The total number of wins of the opponents that the team has beaten.
+    StrengthOfVictory,
+    /// The total points of all the opponents that the team has played, whether they won or lost.
+    /// Mostly useful for Swiss stages, where teams with the same record may have had easier or harder opponents.
+    Buchholz,
+}
+
+/// The number of games played in each match of a stage, e.g. best-of-three.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum SeriesFormat {
+    /// The match ends as soon as one team has won a majority of the games, e.g. BestOf(3) allows 2-0 or 2-1.
+    /// Should be an odd number, so that there is always a winner.
+    BestOf(u32),
+    /// Exactly this many games are always played, so the match may be drawn, e.g. FixedGames(2) allows 2-0, 1-1 or 0-2.
+    FixedGames(u32),
+}
+
+/// How teams are ranked in the standings, based on their results.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum ScoringSystem {
+    /// Teams are ranked by their number of wins, and then by fewest losses. Draws count for nothing.
+    WinLoss,
+    /// Teams get points for each match depending on the result, e.g. 3 for a win, 1 for a draw and 0 for a loss,
+    /// and are ranked by their total.
+    Points {
+        win: i64,
+        draw: i64,
+        loss: i64,
+        /// Extra points for winning without dropping a game, e.g. 2-0 in a best-of-three.
+        #[serde(default = "default_sweep_bonus_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+        sweep_bonus: i64,
+    },
+    /// Teams get points for each game they win within a match (e.g. 2 points for winning 2-1 and 1 point for losing 1-2),
+    /// and are ranked by their total.
+    GamePoints {
+        per_game: i64,
+        /// Extra points for winning without dropping a game, e.g. 2-0 in a best-of-three.
+        sweep_bonus: i64,
+    },
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
 pub enum Outcome {
     Winner, Loser,
 }
 
+/// A match in a round robin's schedule. Once it has been played, this refers to the actual match.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScheduledMatch {
+    pub team_a: TeamId,
+    pub team_b: TeamId,
+    /// Which round of the schedule (e.g. week or day) this is to be played in, starting from 0.
+    pub round: u32,
+    /// The result of this scheduled match, once it has been played.
+    pub match_id: Option<MatchId>,
+    /// When this is going to be played. This is just for display, so can be anything, e.g. "Sat 18:00".
+    #[serde(default = "default_time_for_deserialization")] // This field was added, so give it a default value so that we can deserialize old data
+    pub time: Option<String>,
+}
+
+/// Two teams playing each other in a round of a Swiss stage. Unlike a fixture, the teams are always known
+/// as they are decided when the round is generated.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SwissPairing {
+    pub team_a: TeamId,
+    pub team_b: TeamId,
+    /// The result of this pairing, once it has been played.
+    pub match_id: Option<MatchId>,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
 pub enum FixtureInput {
     TeamA, TeamB,
@@ -98,15 +218,253 @@ impl Tournament {
     pub fn new(id: TournamentId, name: String) -> Tournament {
         Tournament { id, name, stages: indexmap![] }
     }
+
+    /// Changes every ID in this tournament (including its stages, teams, matches and fixtures) using the given mapping.
+    /// References between records (e.g. a match's teams) are updated to match, so the mapping must give
+    /// the same result each time it is called with the same ID.
+    pub fn remap_ids(&mut self, f: &mut dyn FnMut(usize) -> usize) {
+        self.id = f(self.id);
+        let old_stages = std::mem::take(&mut self.stages);
+        for (_, mut stage) in old_stages {
+            stage.remap_ids(self.id, f);
+            self.stages.insert(stage.id, stage);
+        }
+    }
 }
 
 impl Stage {
-    pub fn new_round_robin(id: StageId, tournament_id: TournamentId, name: String) -> Stage {
-        Stage { id, tournament_id, name, teams: indexmap![], matches: indexmap![], kind: StageKind::RoundRobin {  } }
+    pub fn new_round_robin(id: StageId, tournament_id: TournamentId, name: String, legs: u32) -> Stage {
+        Stage { id, tournament_id, name, teams: indexmap![], matches: indexmap![], kind: StageKind::RoundRobin { legs, schedule: vec![], round_labels: indexmap![] }, tiebreakers: Tiebreaker::defaults(),
+            series_format: SeriesFormat::BestOf(1), scoring_system: ScoringSystem::WinLoss, advancing_teams: 0 }
     }
 
     pub fn new_bracket(id: StageId, tournament_id: TournamentId, name: String) -> Stage {
-        Stage { id, tournament_id, name, teams: indexmap![], matches: indexmap![], kind: StageKind::Bracket { fixtures: indexmap![] } }
+        Stage { id, tournament_id, name, teams: indexmap![], matches: indexmap![], kind: StageKind::Bracket { fixtures: indexmap![] }, tiebreakers: Tiebreaker::defaults(),
+            series_format: SeriesFormat::BestOf(1), scoring_system: ScoringSystem::WinLoss, advancing_teams: 0 }
+    }
+
+    pub fn new_swiss(id: StageId, tournament_id: TournamentId, name: String, rounds: u32, advance_wins: u32, eliminate_losses: u32) -> Stage {
+        Stage { id, tournament_id, name, teams: indexmap![], matches: indexmap![], kind: StageKind::Swiss { rounds, advance_wins, eliminate_losses, pairings: vec![] },
+            tiebreakers: vec![Tiebreaker::Buchholz, Tiebreaker::HeadToHead, Tiebreaker::ScoreDifferential],
+            series_format: SeriesFormat::BestOf(1), scoring_system: ScoringSystem::WinLoss, advancing_teams: 0 }
+    }
+
+    fn remap_ids(&mut self, tournament_id: TournamentId, f: &mut dyn FnMut(usize) -> usize) {
+        self.id = f(self.id);
+        self.tournament_id = tournament_id;
+
+        let old_teams = std::mem::take(&mut self.teams);
+        for (_, mut team) in old_teams {
+            team.id = f(team.id);
+            self.teams.insert(team.id, team);
+        }
+
+        let old_matches = std::mem::take(&mut self.matches);
+        for (_, mut m) in old_matches {
+            m.id = f(m.id);
+            m.team_a = f(m.team_a);
+            m.team_b = f(m.team_b);
+            self.matches.insert(m.id, m);
+        }
+
+        if let StageKind::Bracket { fixtures } = &mut self.kind {
+            let old_fixtures = std::mem::take(fixtures);
+            for (_, mut fixture) in old_fixtures {
+                fixture.id = f(fixture.id);
+                fixture.match_id = fixture.match_id.map(&mut *f);
+                fixture.team_a.remap_ids(f);
+                fixture.team_b.remap_ids(f);
+                fixtures.insert(fixture.id, fixture);
+            }
+        }
+        if let StageKind::RoundRobin { schedule, .. } = &mut self.kind {
+            for m in schedule {
+                m.team_a = f(m.team_a);
+                m.team_b = f(m.team_b);
+                m.match_id = m.match_id.map(&mut *f);
+            }
+        }
+        if let StageKind::Swiss { pairings, .. } = &mut self.kind {
+            for p in pairings.iter_mut().flatten() {
+                p.team_a = f(p.team_a);
+                p.team_b = f(p.team_b);
+                p.match_id = p.match_id.map(&mut *f);
+            }
+        }
+    }
+
+    /// Gets the matches which count towards the teams' records, i.e. everything except tiebreaker matches.
+    pub fn get_regular_matches(&self) -> impl Iterator<Item = &Match> {
+        self.matches.values().filter(|m| !m.is_tiebreaker)
+    }
+
+    pub fn get_tiebreaker_matches(&self) -> impl Iterator<Item = &Match> {
+        self.matches.values().filter(|m| m.is_tiebreaker)
+    }
+
+    /// Gets the number of matches won and lost by the given team in this stage, not counting tiebreaker matches.
+    pub fn get_win_loss(&self, team_id: TeamId) -> (usize, usize) {
+        let w = self.get_regular_matches().filter(|m| m.get_winner() == Some(team_id)).count();
+        let l = self.get_regular_matches().filter(|m| m.get_loser() == Some(team_id)).count();
+        (w, l)
+    }
+
+    /// Gets the number of matches drawn by the given team in this stage, not counting tiebreaker matches.
+    pub fn get_draws(&self, team_id: TeamId) -> usize {
+        self.get_regular_matches().filter(|m| m.contains(team_id) && m.is_draw()).count()
+    }
+
+    /// Gets the number of points the given team has earned in this stage, using the stage's scoring system.
+    /// For a win/loss scoring system this is just the number of wins.
+    pub fn get_points(&self, team_id: TeamId) -> i64 {
+        self.get_regular_matches().filter(|m| m.contains(team_id)).map(|m| self.scoring_system.get_match_points(m, team_id)).sum()
+    }
+
+    /// Whether draws need to be shown for this stage, i.e. whether any matches can be or have been drawn.
+    pub fn has_draws(&self) -> bool {
+        self.series_format.get_draw_score().is_some() || self.matches.values().any(|m| m.is_draw())
+    }
+
+    /// Gets the team which is playing in a fixture, if it is known. For linked teams, this is only known once the
+    /// previous fixture has been played and had a winner - a drawn match doesn't decide who goes through.
+    pub fn get_fixture_team(&self, fixture_team: &FixtureTeam) -> Option<TeamId> {
+        match fixture_team {
+            FixtureTeam::Fixed(t) => Some(*t),
+            FixtureTeam::Linked { fixture_id, outcome } => {
+                let fixtures = match &self.kind {
+                    StageKind::Bracket { fixtures } => fixtures,
+                    _ => return None,
+                };
+                let m = fixtures.get(fixture_id)?.match_id.and_then(|m| self.matches.get(&m))?;
+                match outcome {
+                    Outcome::Winner => m.get_winner(),
+                    Outcome::Loser => m.get_loser(),
+                }
+            }
+        }
+    }
+
+    /// Gets the teams which have advanced out of a bracket, e.g. the top two of a GSL group, in the order of their fixtures.
+    /// This is only known for fixtures which record their exits (see Fixture::exits). Empty for stages which aren't brackets.
+    pub fn get_advanced_teams(&self) -> Vec<TeamId> {
+        self.get_bracket_exits(BracketExit::Advance)
+    }
+
+    /// Gets the teams which have been eliminated from a bracket, in the order of their fixtures.
+    /// This is only known for fixtures which record their exits (see Fixture::exits). Empty for stages which aren't brackets.
+    pub fn get_eliminated_teams(&self) -> Vec<TeamId> {
+        self.get_bracket_exits(BracketExit::Eliminate)
+    }
+
+    /// Gets the teams which have left the bracket in the given way from each played fixture.
+    fn get_bracket_exits(&self, exit: BracketExit) -> Vec<TeamId> {
+        let fixtures = match &self.kind {
+            StageKind::Bracket { fixtures } => fixtures,
+            _ => return vec![],
+        };
+        fixtures.values()
+            .flat_map(|f| f.exits.iter().filter(|(_, e)| *e == exit).map(|(outcome, _)| FixtureTeam::Linked { fixture_id: f.id, outcome: *outcome }))
+            .filter_map(|t| self.get_fixture_team(&t))
+            .collect()
+    }
+
+    /// Removes the results of any fixtures which no longer match the teams that should be playing in them, e.g. because
+    /// the result of an earlier fixture was changed so that a different team goes through. This cascades through the bracket,
+    /// as removing a result means that any later fixtures linked to it no longer know their teams either.
+    /// For a Swiss stage, this just forgets the results of any pairings whose match has been deleted, as the teams never change.
+    pub fn clear_invalid_fixture_results(&mut self) {
+        if let StageKind::Swiss { pairings, .. } = &mut self.kind {
+            for p in pairings.iter_mut().flatten() {
+                if !p.match_id.and_then(|m| self.matches.get(&m)).is_some_and(|m| m.is_between(p.team_a, p.team_b)) {
+                    p.match_id = None;
+                }
+            }
+            return;
+        }
+        loop {
+            let invalid: Vec<FixtureId> = match &self.kind {
+                StageKind::Bracket { fixtures } => fixtures.values().filter(|f| match f.match_id {
+                    Some(m) => match (self.matches.get(&m), self.get_fixture_team(&f.team_a), self.get_fixture_team(&f.team_b)) {
+                        (Some(m), Some(a), Some(b)) => !m.is_between(a, b),
+                        _ => true,
+                    },
+                    None => false,
+                }).map(|f| f.id).collect(),
+                _ => return,
+            };
+            if invalid.is_empty() {
+                return;
+            }
+
+            if let StageKind::Bracket { fixtures } = &mut self.kind {
+                for fixture_id in invalid {
+                    if let Some(m) = fixtures.get_mut(&fixture_id).and_then(|f| f.match_id.take()) {
+                        self.matches.shift_remove(&m);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gets the name to show for a round of a round robin's schedule, e.g. "Week 1", or "Round 1" if it hasn't been named.
+    pub fn get_round_label(&self, round: u32) -> String {
+        let label = match &self.kind {
+            StageKind::RoundRobin { round_labels, .. } => round_labels.get(&round).cloned(),
+            _ => None,
+        };
+        label.unwrap_or(format!("Round {}", round + 1))
+    }
+
+    /// Gets the scheduled matches between the two given teams which haven't been played yet, in the order they are scheduled.
+    pub fn get_unplayed_scheduled_matches_between(&self, a: TeamId, b: TeamId) -> Vec<&ScheduledMatch> {
+        match &self.kind {
+            StageKind::RoundRobin { schedule, .. } => schedule.iter().filter(|m| m.match_id.is_none() && ([m.team_a, m.team_b] == [a, b] || [m.team_a, m.team_b] == [b, a])).collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Keeps a round robin's schedule in step with the stage's matches. Scheduled matches whose match has been deleted
+    /// (or made a tiebreaker) become unplayed again, and any new matches are linked to the first unplayed scheduled match
+    /// between the same teams, so results entered without going through the schedule still count as playing it.
+    pub fn sync_schedule(&mut self) {
+        if let StageKind::RoundRobin { schedule, .. } = &mut self.kind {
+            for m in schedule.iter_mut() {
+                if !m.match_id.and_then(|id| self.matches.get(&id)).is_some_and(|x| !x.is_tiebreaker && x.is_between(m.team_a, m.team_b)) {
+                    m.match_id = None;
+                }
+            }
+            for x in self.matches.values().filter(|x| !x.is_tiebreaker) {
+                if schedule.iter().any(|m| m.match_id == Some(x.id)) {
+                    continue;
+                }
+                if let Some(m) = schedule.iter_mut().find(|m| m.match_id.is_none() && x.is_between(m.team_a, m.team_b)) {
+                    m.match_id = Some(x.id);
+                }
+            }
+        }
+    }
+
+    /// Gets the total of the given team's scores minus their opponents' scores, across all their matches in this stage
+    /// (not counting tiebreaker matches).
+    pub fn get_score_differential(&self, team_id: TeamId) -> i64 {
+        let mut result = 0;
+        for m in self.get_regular_matches() {
+            if m.team_a == team_id {
+                result += m.team_a_score as i64 - m.team_b_score as i64;
+            } else if m.team_b == team_id {
+                result += m.team_b_score as i64 - m.team_a_score as i64;
+            }
+        }
+        result
+    }
+
+    /// Gets all the (non-tiebreaker) matches between the two given teams, in the order they were played.
+    pub fn get_matches_between(&self, a: TeamId, b: TeamId) -> Vec<&Match> {
+        self.get_regular_matches().filter(|m| m.is_between(a, b)).collect()
+    }
+
+    pub fn get_games_played(&self, team_id: TeamId) -> usize {
+        self.get_regular_matches().filter(|m| m.contains(team_id)).count()
     }
 }
 
@@ -126,6 +484,10 @@ impl Match {
         self.team_a == t || self.team_b == t
     }
 
+    pub fn is_draw(&self) -> bool {
+        self.team_a_score == self.team_b_score
+    }
+
     pub fn get_winner(&self) -> Option<TeamId> {
         match self.team_a_score.cmp(&self.team_b_score) {
             std::cmp::Ordering::Less => Some(self.team_b),
@@ -143,16 +505,536 @@ impl Match {
     }
 }
 
+impl Tiebreaker {
+    pub const ALL: [Tiebreaker; 5] = [Tiebreaker::HeadToHead, Tiebreaker::HeadToHeadMiniTable, Tiebreaker::ScoreDifferential, Tiebreaker::StrengthOfVictory,
+        Tiebreaker::Buchholz];
+
+    pub fn defaults() -> Vec<Tiebreaker> {
+        vec![Tiebreaker::HeadToHead, Tiebreaker::HeadToHeadMiniTable, Tiebreaker::ScoreDifferential, Tiebreaker::StrengthOfVictory]
+    }
+
+    /// A short name, used for displaying and for the user to enter.
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Tiebreaker::HeadToHead => "head-to-head",
+            Tiebreaker::HeadToHeadMiniTable => "mini-table",
+            Tiebreaker::ScoreDifferential => "score-differential",
+            Tiebreaker::StrengthOfVictory => "strength-of-victory",
+            Tiebreaker::Buchholz => "buchholz",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Tiebreaker> {
+        Tiebreaker::ALL.into_iter().find(|t| t.get_name() == name)
+    }
+}
+
+impl SeriesFormat {
+    /// Checks that the format itself makes sense, e.g. a best-of series needs an odd number of games.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            SeriesFormat::BestOf(n) => n % 2 == 1,
+            SeriesFormat::FixedGames(n) => *n > 0,
+        }
+    }
+
+    /// Checks whether a completed match in this format could have finished with the given score.
+    pub fn is_valid_score(&self, team_a_score: u32, team_b_score: u32) -> bool {
+        match self {
+            SeriesFormat::BestOf(n) => {
+                let wins_needed = n / 2 + 1;
+                std::cmp::max(team_a_score, team_b_score) == wins_needed && std::cmp::min(team_a_score, team_b_score) < wins_needed
+            }
+            SeriesFormat::FixedGames(n) => team_a_score + team_b_score == *n,
+        }
+    }
+
+    /// The score (winner first) of a match in this format where the winner won every game, e.g. 2-0 for a best-of-three.
+    pub fn get_sweep_score(&self) -> (u32, u32) {
+        match self {
+            SeriesFormat::BestOf(n) => (n / 2 + 1, 0),
+            SeriesFormat::FixedGames(n) => (*n, 0),
+        }
+    }
+
+    /// The score of a drawn match in this format, if draws are possible, e.g. 1-1 for two fixed games.
+    pub fn get_draw_score(&self) -> Option<(u32, u32)> {
+        match self {
+            SeriesFormat::FixedGames(n) if n % 2 == 0 => Some((n / 2, n / 2)),
+            _ => None,
+        }
+    }
+
+    /// Every score that a completed match in this format could finish with, e.g. 2-0, 2-1, 1-2 and 0-2 for a best-of-three.
+    pub fn get_possible_scores(&self) -> Vec<(u32, u32)> {
+        match self {
+            SeriesFormat::BestOf(n) => {
+                let wins_needed = n / 2 + 1;
+                let mut result: Vec<(u32, u32)> = (0..wins_needed).map(|l| (wins_needed, l)).collect();
+                result.extend((0..wins_needed).rev().map(|l| (l, wins_needed)));
+                result
+            }
+            SeriesFormat::FixedGames(n) => (0..=*n).rev().map(|a| (a, n - a)).collect(),
+        }
+    }
+
+    /// A short name, used for displaying and for the user to enter, e.g. "bo3" or "fixed-2".
+    pub fn get_name(&self) -> String {
+        match self {
+            SeriesFormat::BestOf(n) => format!("bo{n}"),
+            SeriesFormat::FixedGames(n) => format!("fixed-{n}"),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SeriesFormat> {
+        let result = if let Some(n) = name.strip_prefix("bo") {
+            SeriesFormat::BestOf(n.parse().ok()?)
+        } else if let Some(n) = name.strip_prefix("fixed-") {
+            SeriesFormat::FixedGames(n.parse().ok()?)
+        } else {
+            return None;
+        };
+        if result.is_valid() { Some(result) } else { None }
+    }
+}
+
+impl ScoringSystem {
+    /// The default points for football-style leagues.
+    pub const THREE_ONE_ZERO: ScoringSystem = ScoringSystem::Points { win: 3, draw: 1, loss: 0, sweep_bonus: 0 };
+
+    /// Whether teams are ranked by a points total, which should be shown in the standings.
+    pub fn uses_points(&self) -> bool {
+        !matches!(self, ScoringSystem::WinLoss)
+    }
+
+    /// The number of points that the given team earns from the given match. For a win/loss scoring system,
+    /// this is 1 for a win and 0 otherwise.
+    pub fn get_match_points(&self, m: &Match, team_id: TeamId) -> i64 {
+        let (team_score, opponent_score) = if m.team_a == team_id { (m.team_a_score, m.team_b_score) } else { (m.team_b_score, m.team_a_score) };
+        self.get_score_points(team_score, opponent_score)
+    }
+
+    /// The number of points that a team earns from a match which they finished with the given score.
+    pub fn get_score_points(&self, team_score: u32, opponent_score: u32) -> i64 {
+        let is_sweep = team_score > opponent_score && opponent_score == 0;
+        match self {
+            ScoringSystem::WinLoss => if team_score > opponent_score { 1 } else { 0 },
+            ScoringSystem::Points { win, draw, loss, sweep_bonus } => {
+                let result_points = match team_score.cmp(&opponent_score) {
+                    std::cmp::Ordering::Greater => *win,
+                    std::cmp::Ordering::Equal => *draw,
+                    std::cmp::Ordering::Less => *loss,
+                };
+                result_points + if is_sweep { *sweep_bonus } else { 0 }
+            }
+            ScoringSystem::GamePoints { per_game, sweep_bonus } => team_score as i64 * per_game + if is_sweep { *sweep_bonus } else { 0 },
+        }
+    }
+
+    /// A short name, used for displaying and for the user to enter, e.g. "win-loss", "points:3/1/0" or "games:1".
+    /// Any sweep bonus is added on the end, e.g. "games:1+1".
+    pub fn get_name(&self) -> String {
+        let (name, sweep_bonus) = match self {
+            ScoringSystem::WinLoss => ("win-loss".to_string(), 0),
+            ScoringSystem::Points { win, draw, loss, sweep_bonus } => (format!("points:{win}/{draw}/{loss}"), *sweep_bonus),
+            ScoringSystem::GamePoints { per_game, sweep_bonus } => (format!("games:{per_game}"), *sweep_bonus),
+        };
+        if sweep_bonus != 0 { format!("{name}+{sweep_bonus}") } else { name }
+    }
+
+    pub fn from_name(name: &str) -> Option<ScoringSystem> {
+        if name == "win-loss" {
+            return Some(ScoringSystem::WinLoss);
+        }
+        let (name, sweep_bonus) = match name.split_once('+') {
+            Some((n, b)) => (n, b.trim().parse().ok()?),
+            None => (name, 0),
+        };
+        if let Some(per_game) = name.strip_prefix("games:") {
+            return Some(ScoringSystem::GamePoints { per_game: per_game.trim().parse().ok()?, sweep_bonus });
+        }
+        let points: Vec<Option<i64>> = name.strip_prefix("points:")?.split('/').map(|p| p.trim().parse().ok()).collect();
+        match points[..] {
+            [Some(win), Some(draw), Some(loss)] => Some(ScoringSystem::Points { win, draw, loss, sweep_bonus }),
+            _ => None,
+        }
+    }
+}
+
 impl FixtureTeam {
     pub fn to_pretty_desc(&self, stage: &Stage) -> String {
         match self {
-            FixtureTeam::Fixed(t) => stage.teams.get(t).and_then(|t| Some(t.name.clone())).unwrap_or("???".to_string()),
-            FixtureTeam::Linked{ fixture_id, outcome } => format!("{outcome:?} of fixture {fixture_id}"),
+            FixtureTeam::Fixed(t) => stage.teams.get(t).map(|t| t.name.clone()).unwrap_or("???".to_string()),
+            // Once the previous fixture has been played, show who actually goes through
+            FixtureTeam::Linked{ fixture_id, outcome } => match stage.get_fixture_team(self).and_then(|t| stage.teams.get(&t)) {
+                Some(t) => t.name.clone(),
+                None => format!("{outcome:?} of fixture {fixture_id}"),
+            }
+        }
+    }
+
+    fn remap_ids(&mut self, f: &mut dyn FnMut(usize) -> usize) {
+        match self {
+            FixtureTeam::Fixed(t) => *t = f(*t),
+            FixtureTeam::Linked { fixture_id, .. } => *fixture_id = f(*fixture_id),
         }
     }
 }
 
 // This field was added, so give it a default value so that we can deserialize old data
 fn default_stage_kind_for_deserialization() -> StageKind {
-    StageKind::RoundRobin {  }
-}
\ No newline at end of file
+    StageKind::RoundRobin { legs: 1, schedule: vec![], round_labels: indexmap![] }
+}
+
+// This field was added, so give it a default value so that we can deserialize old data
+fn default_series_format_for_deserialization() -> SeriesFormat {
+    SeriesFormat::BestOf(1)
+}
+
+// This field was added, so give it a default value so that we can deserialize old data
+fn default_scoring_system_for_deserialization() -> ScoringSystem {
+    ScoringSystem::WinLoss
+}
+
+// This field was added, so give it a default value so that we can deserialize old data
+fn default_sweep_bonus_for_deserialization() -> i64 {
+    0
+}
+
+// This field was added, so give it a default value so that we can deserialize old data
+fn default_legs_for_deserialization() -> u32 {
+    1
+}
+
+// This field was added, so give it a default value so that we can deserialize old data
+fn default_exits_for_deserialization() -> Vec<(Outcome, BracketExit)> {
+    vec![]
+}
+
+// This field was added, so give it a default value so that we can deserialize old data
+fn default_schedule_for_deserialization() -> Vec<ScheduledMatch> {
+    vec![]
+}
+
+// This field was added, so give it a default value so that we can deserialize old data
+fn default_round_labels_for_deserialization() -> IndexMap<u32, String> {
+    indexmap![]
+}
+
+// This field was added, so give it a default value so that we can deserialize old data
+fn default_time_for_deserialization() -> Option<String> {
+    None
+}
+
+// This field was added, so give it a default value so that we can deserialize old data
+fn default_advancing_teams_for_deserialization() -> u32 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+    use crate::model::tournament::{Fixture, FixtureId, FixtureTeam, MatchId, Outcome, ScoringSystem, SeriesFormat, Stage, StageId, StageKind, TeamId, TournamentId};
+
+    #[test]
+    fn series_format_scores() {
+        // None of the formats here can have a score above 4
+        let valid = |f: SeriesFormat| (0..=4).flat_map(|a| (0..=4).map(move |b| (a, b))).filter(|&(a, b)| f.is_valid_score(a, b)).collect::<Vec<_>>();
+        assert_eq!(valid(SeriesFormat::BestOf(1)), vec![(0, 1), (1, 0)]);
+        assert_eq!(valid(SeriesFormat::BestOf(3)), vec![(0, 2), (1, 2), (2, 0), (2, 1)]);
+        assert_eq!(valid(SeriesFormat::FixedGames(2)), vec![(0, 2), (1, 1), (2, 0)]);
+        assert_eq!(valid(SeriesFormat::FixedGames(3)), vec![(0, 3), (1, 2), (2, 1), (3, 0)]);
+
+        for f in [SeriesFormat::BestOf(1), SeriesFormat::BestOf(3), SeriesFormat::BestOf(5), SeriesFormat::FixedGames(2), SeriesFormat::FixedGames(3)] {
+            let mut possible = f.get_possible_scores();
+            possible.sort();
+            assert_eq!(possible, valid(f));
+            assert!(f.is_valid_score(f.get_sweep_score().0, f.get_sweep_score().1));
+        }
+        assert_eq!(SeriesFormat::FixedGames(2).get_draw_score(), Some((1, 1)));
+        assert_eq!(SeriesFormat::FixedGames(3).get_draw_score(), None);
+        assert_eq!(SeriesFormat::BestOf(3).get_draw_score(), None);
+    }
+
+    #[test]
+    fn series_format_names() {
+        for f in [SeriesFormat::BestOf(1), SeriesFormat::BestOf(5), SeriesFormat::FixedGames(2)] {
+            assert_eq!(SeriesFormat::from_name(&f.get_name()), Some(f));
+        }
+        assert_eq!(SeriesFormat::from_name("bo3"), Some(SeriesFormat::BestOf(3)));
+        // A best-of series needs an odd number of games, so that someone always wins
+        assert_eq!(SeriesFormat::from_name("bo2"), None);
+        assert_eq!(SeriesFormat::from_name("fixed-0"), None);
+        assert_eq!(SeriesFormat::from_name("best-of-3"), None);
+    }
+
+    #[test]
+    fn invalid_scores_are_refused() {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 1).unwrap();
+        let [a, b] = ["A", "B"].map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        assert_eq!(model.add_match(t, s, a, b, 2, 0), None);
+        assert_eq!(model.add_match(t, s, a, b, 0, 0), None);
+        let m = model.add_match(t, s, a, b, 1, 0).unwrap();
+        assert!(model.set_match_score(t, s, m, 1, 1).is_err());
+
+        // The 1-0 isn't possible in a best-of-three, so the format can't be changed to that
+        assert!(model.set_stage_series_format(t, s, SeriesFormat::BestOf(3)).is_err());
+        assert!(model.set_stage_series_format(t, s, SeriesFormat::BestOf(2)).is_err());
+        assert_eq!(model.get_stage(t, s).unwrap().series_format, SeriesFormat::BestOf(1));
+        // Whereas it is for a single fixed game
+        model.set_stage_series_format(t, s, SeriesFormat::FixedGames(1)).unwrap();
+
+        model.delete_match(t, s, m).unwrap();
+        model.set_stage_series_format(t, s, SeriesFormat::BestOf(3)).unwrap();
+        assert_eq!(model.add_match(t, s, a, b, 1, 0), None);
+        assert!(model.add_match(t, s, a, b, 2, 1).is_some());
+    }
+    #[test]
+    fn scoring_system_points() {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 1).unwrap();
+        model.set_stage_series_format(t, s, SeriesFormat::BestOf(3)).unwrap();
+        let [a, b, c] = ["A", "B", "C"].map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        model.add_match(t, s, a, b, 2, 0).unwrap();
+        model.add_match(t, s, a, c, 2, 1).unwrap();
+        model.add_match(t, s, c, b, 1, 2).unwrap();
+
+        let mut get_points = |scoring_system| {
+            model.set_stage_scoring_system(t, s, scoring_system).unwrap();
+            let stage = model.get_stage(t, s).unwrap();
+            [a, b, c].map(|team| stage.get_points(team))
+        };
+        assert_eq!(get_points(ScoringSystem::WinLoss), [2, 1, 0]);
+        assert_eq!(get_points(ScoringSystem::THREE_ONE_ZERO), [6, 3, 0]);
+        // Only A's 2-0 is a sweep
+        assert_eq!(get_points(ScoringSystem::Points { win: 3, draw: 1, loss: 0, sweep_bonus: 1 }), [7, 3, 0]);
+        assert_eq!(get_points(ScoringSystem::Points { win: 2, draw: 1, loss: -1, sweep_bonus: 0 }), [4, 1, -2]);
+        assert_eq!(get_points(ScoringSystem::GamePoints { per_game: 1, sweep_bonus: 0 }), [4, 2, 2]);
+        assert_eq!(get_points(ScoringSystem::GamePoints { per_game: 1, sweep_bonus: 1 }), [5, 2, 2]);
+    }
+
+    #[test]
+    fn scoring_system_names() {
+        assert_eq!(ScoringSystem::from_name("win-loss"), Some(ScoringSystem::WinLoss));
+        assert_eq!(ScoringSystem::from_name("points:3/1/0"), Some(ScoringSystem::THREE_ONE_ZERO));
+        assert_eq!(ScoringSystem::from_name("points: 2 / 1 / -1"), Some(ScoringSystem::Points { win: 2, draw: 1, loss: -1, sweep_bonus: 0 }));
+        assert_eq!(ScoringSystem::from_name("points:3/1/0+1"), Some(ScoringSystem::Points { win: 3, draw: 1, loss: 0, sweep_bonus: 1 }));
+        assert_eq!(ScoringSystem::from_name("games:1+1"), Some(ScoringSystem::GamePoints { per_game: 1, sweep_bonus: 1 }));
+        for name in ["win-loss", "points:3/1/0", "points:3/1/0+1", "games:1", "games:1+1", "games:2+-1"] {
+            assert_eq!(ScoringSystem::from_name(name).unwrap().get_name(), name);
+        }
+        for name in ["points", "points:3/1", "points:3/1/0/0", "points:a/1/0", "points:3/1/0+", "games:", "games:1+x", "wins"] {
+            assert_eq!(ScoringSystem::from_name(name), None, "{name}");
+        }
+    }
+    /// A bracket with two semi-finals (A v B and C v D), and a final and third place match linked to them.
+    /// The fixtures are returned in that order.
+    fn new_bracket() -> (Model, TournamentId, StageId, [TeamId; 4], [FixtureId; 4]) {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_bracket(t, "Playoffs".to_string()).unwrap();
+        let [a, b, c, d] = ["A", "B", "C", "D"].map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        let semi_1 = model.add_fixture(t, s, (0, 0), FixtureTeam::Fixed(a), FixtureTeam::Fixed(b)).unwrap();
+        let semi_2 = model.add_fixture(t, s, (0, 100), FixtureTeam::Fixed(c), FixtureTeam::Fixed(d)).unwrap();
+        let linked = |fixture_id, outcome| FixtureTeam::Linked { fixture_id, outcome };
+        let final_ = model.add_fixture(t, s, (200, 50), linked(semi_1, Outcome::Winner), linked(semi_2, Outcome::Winner)).unwrap();
+        let third_place = model.add_fixture(t, s, (200, 150), linked(semi_1, Outcome::Loser), linked(semi_2, Outcome::Loser)).unwrap();
+        (model, t, s, [a, b, c, d], [semi_1, semi_2, final_, third_place])
+    }
+
+    fn get_fixture(stage: &Stage, fixture_id: FixtureId) -> &Fixture {
+        match &stage.kind {
+            StageKind::Bracket { fixtures } => &fixtures[&fixture_id],
+            _ => panic!("Not a bracket"),
+        }
+    }
+
+    /// The teams playing in a fixture, if they're known.
+    fn get_fixture_teams(model: &Model, t: TournamentId, s: StageId, fixture_id: FixtureId) -> (Option<TeamId>, Option<TeamId>) {
+        let stage = model.get_stage(t, s).unwrap();
+        let f = get_fixture(stage, fixture_id);
+        (stage.get_fixture_team(&f.team_a), stage.get_fixture_team(&f.team_b))
+    }
+
+    #[test]
+    fn fixture_results_decide_linked_teams() {
+        let (mut model, t, s, [a, b, c, d], [semi_1, semi_2, final_, third_place]) = new_bracket();
+        assert_eq!(get_fixture_teams(&model, t, s, final_), (None, None));
+        let stage = model.get_stage(t, s).unwrap();
+        assert_eq!(get_fixture(stage, final_).team_a.to_pretty_desc(stage), format!("Winner of fixture {semi_1}"));
+
+        model.set_fixture_score(t, s, semi_1, 1, 0).unwrap();
+        assert_eq!(get_fixture_teams(&model, t, s, final_), (Some(a), None));
+        assert_eq!(get_fixture_teams(&model, t, s, third_place), (Some(b), None));
+        // The final can't be played until both teams are known
+        assert!(model.set_fixture_score(t, s, final_, 1, 0).is_err());
+
+        // The match is the other way round to the fixture's teams, but the fixture's score is still given in its own order
+        model.set_fixture_score(t, s, semi_2, 0, 1).unwrap();
+        assert_eq!(get_fixture_teams(&model, t, s, final_), (Some(a), Some(d)));
+        assert_eq!(get_fixture_teams(&model, t, s, third_place), (Some(b), Some(c)));
+        let stage = model.get_stage(t, s).unwrap();
+        assert_eq!(get_fixture(stage, final_).team_b.to_pretty_desc(stage), "D");
+
+        model.set_fixture_score(t, s, final_, 0, 1).unwrap();
+        let stage = model.get_stage(t, s).unwrap();
+        let m = &stage.matches[&get_fixture(stage, final_).match_id.unwrap()];
+        assert_eq!(m.get_winner(), Some(d));
+    }
+
+    #[test]
+    fn changing_fixture_results_clears_later_fixtures() {
+        let (mut model, t, s, [_, b, _, _], [semi_1, semi_2, final_, third_place]) = new_bracket();
+        model.set_stage_series_format(t, s, SeriesFormat::BestOf(3)).unwrap();
+        for f in [semi_1, semi_2, final_, third_place] {
+            model.set_fixture_score(t, s, f, 2, 0).unwrap();
+        }
+        let get_match_id = |model: &Model, f| get_fixture(model.get_stage(t, s).unwrap(), f).match_id;
+        let final_match = get_match_id(&model, final_).unwrap();
+        let semi_1_match = get_match_id(&model, semi_1).unwrap();
+
+        // Changing the score without changing the winner doesn't affect anything
+        model.set_fixture_score(t, s, semi_1, 2, 1).unwrap();
+        assert_eq!(get_match_id(&model, final_), Some(final_match));
+
+        // B now goes through instead, so both the final and the third place match had the wrong teams
+        model.set_fixture_score(t, s, semi_1, 1, 2).unwrap();
+        assert_eq!(get_match_id(&model, semi_1), Some(semi_1_match));
+        assert_eq!(get_match_id(&model, final_), None);
+        assert_eq!(get_match_id(&model, third_place), None);
+        assert!(!model.get_stage(t, s).unwrap().matches.contains_key(&final_match));
+        assert_eq!(model.get_stage(t, s).unwrap().matches.len(), 2);
+        assert_eq!(get_fixture_teams(&model, t, s, final_).0, Some(b));
+
+        // Deleting the semi-final's match means nobody is known to go through at all
+        model.set_fixture_score(t, s, final_, 2, 0).unwrap();
+        model.delete_match(t, s, semi_1_match).unwrap();
+        assert_eq!(get_match_id(&model, semi_1), None);
+        assert_eq!(get_match_id(&model, final_), None);
+        assert_eq!(get_fixture_teams(&model, t, s, final_).0, None);
+        assert_eq!(model.get_stage(t, s).unwrap().matches.len(), 1);
+        assert!(get_match_id(&model, semi_2).is_some());
+    }
+
+    #[test]
+    fn fixtures_cant_be_drawn() {
+        let (mut model, t, s, _, [semi_1, ..]) = new_bracket();
+        model.set_stage_series_format(t, s, SeriesFormat::FixedGames(2)).unwrap();
+        assert!(model.set_fixture_score(t, s, semi_1, 1, 1).is_err());
+        assert!(model.get_stage(t, s).unwrap().matches.is_empty());
+
+        // Nor can an existing result be changed to a draw
+        model.set_fixture_score(t, s, semi_1, 2, 0).unwrap();
+        assert!(model.set_fixture_score(t, s, semi_1, 1, 1).is_err());
+        assert!(model.get_stage(t, s).unwrap().matches.values().all(|m| m.get_winner().is_some()));
+    }
+
+    /// A double round robin between three teams, with its schedule generated.
+    fn new_schedule() -> (Model, TournamentId, StageId, [TeamId; 3]) {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 2).unwrap();
+        let teams = ["A", "B", "C"].map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        model.generate_round_robin_schedule(t, s).unwrap();
+        (model, t, s, teams)
+    }
+
+    /// The indexes in the schedule of the matches between the two teams, in order, along with the match each is linked to.
+    fn get_links(model: &Model, t: TournamentId, s: StageId, a: TeamId, b: TeamId) -> Vec<(usize, Option<MatchId>)> {
+        match &model.get_stage(t, s).unwrap().kind {
+            StageKind::RoundRobin { schedule, .. } => schedule.iter().enumerate()
+                .filter(|(_, m)| [m.team_a, m.team_b] == [a, b] || [m.team_a, m.team_b] == [b, a])
+                .map(|(i, m)| (i, m.match_id))
+                .collect(),
+            _ => panic!("Not a round robin"),
+        }
+    }
+
+    #[test]
+    fn matches_play_the_first_unplayed_scheduled_match() {
+        let (mut model, t, s, [a, b, c]) = new_schedule();
+        let [(first, _), (second, _)] = get_links(&model, t, s, a, b)[..] else { panic!() };
+
+        // Either way round, a match counts as playing the first leg and then the second
+        let m1 = model.add_match(t, s, b, a, 1, 0).unwrap();
+        assert_eq!(get_links(&model, t, s, a, b), vec![(first, Some(m1)), (second, None)]);
+        let m2 = model.add_match(t, s, a, b, 1, 0).unwrap();
+        assert_eq!(get_links(&model, t, s, a, b), vec![(first, Some(m1)), (second, Some(m2))]);
+        // Once the schedule has been played, further matches aren't part of it
+        model.add_match(t, s, a, b, 1, 0).unwrap();
+        assert_eq!(get_links(&model, t, s, a, b), vec![(first, Some(m1)), (second, Some(m2))]);
+        assert!(get_links(&model, t, s, a, c).iter().all(|(_, m)| m.is_none()));
+
+        // Changing the score keeps the link
+        model.set_match_score(t, s, m1, 0, 1).unwrap();
+        assert_eq!(get_links(&model, t, s, a, b), vec![(first, Some(m1)), (second, Some(m2))]);
+    }
+
+    #[test]
+    fn deleting_matches_unplays_scheduled_matches() {
+        let (mut model, t, s, [a, b, _]) = new_schedule();
+        let [(first, _), (second, _)] = get_links(&model, t, s, a, b)[..] else { panic!() };
+        let m1 = model.add_match(t, s, a, b, 1, 0).unwrap();
+        let m2 = model.add_match(t, s, a, b, 0, 1).unwrap();
+
+        model.delete_match(t, s, m1).unwrap();
+        assert_eq!(get_links(&model, t, s, a, b), vec![(first, None), (second, Some(m2))]);
+        // The next match fills the gap, rather than the second leg being played twice
+        let m3 = model.add_match(t, s, a, b, 1, 0).unwrap();
+        assert_eq!(get_links(&model, t, s, a, b), vec![(first, Some(m3)), (second, Some(m2))]);
+
+        // Likewise for a match becoming a tiebreaker, and back again
+        model.set_match_tiebreaker(t, s, m2, true).unwrap();
+        assert_eq!(get_links(&model, t, s, a, b), vec![(first, Some(m3)), (second, None)]);
+        model.set_match_tiebreaker(t, s, m2, false).unwrap();
+        assert_eq!(get_links(&model, t, s, a, b), vec![(first, Some(m3)), (second, Some(m2))]);
+
+        // Tiebreaker matches never count as playing the schedule
+        model.delete_match(t, s, m2).unwrap();
+        model.add_tiebreaker_match(t, s, a, b, 1, 0).unwrap();
+        assert_eq!(get_links(&model, t, s, a, b), vec![(first, Some(m3)), (second, None)]);
+    }
+
+    #[test]
+    fn scheduled_match_scores() {
+        let (mut model, t, s, [a, b, _]) = new_schedule();
+        let [(first, _), (second, _)] = get_links(&model, t, s, a, b)[..] else { panic!() };
+
+        // Entering the second leg's result first links it to the second leg, not the first unplayed one
+        model.set_scheduled_match_score(t, s, second, 1, 0).unwrap();
+        let [(_, None), (_, Some(m2))] = get_links(&model, t, s, a, b)[..] else { panic!() };
+        model.set_scheduled_match_score(t, s, first, 0, 1).unwrap();
+        let [(_, Some(m1)), (_, Some(m))] = get_links(&model, t, s, a, b)[..] else { panic!() };
+        assert_eq!(m, m2);
+        assert_eq!(model.get_stage(t, s).unwrap().matches.len(), 2);
+
+        // Changing a result edits the existing match
+        model.set_scheduled_match_score(t, s, second, 0, 1).unwrap();
+        let stage = model.get_stage(t, s).unwrap();
+        assert_eq!(stage.matches.len(), 2);
+        assert_eq!((stage.matches[&m2].team_a_score, stage.matches[&m2].team_b_score), (0, 1));
+        assert_eq!(get_links(&model, t, s, a, b), vec![(first, Some(m1)), (second, Some(m2))]);
+    }
+
+    #[test]
+    fn adding_and_deleting_scheduled_matches() {
+        let (mut model, t, s, [a, b, _]) = new_schedule();
+        let [(first, _), (second, _)] = get_links(&model, t, s, a, b)[..] else { panic!() };
+        let m1 = model.add_match(t, s, a, b, 1, 0).unwrap();
+
+        // Deleting a played scheduled match keeps the match, which then counts as playing the other leg
+        model.delete_scheduled_match(t, s, first).unwrap();
+        assert_eq!(model.get_stage(t, s).unwrap().matches.len(), 1);
+        assert_eq!(get_links(&model, t, s, a, b), vec![(second - 1, Some(m1))]);
+
+        // A match which isn't part of the schedule counts as playing a newly scheduled match between the same teams
+        let m2 = model.add_match(t, s, b, a, 1, 0).unwrap();
+        let new = model.add_scheduled_match(t, s, b, a, 0).unwrap();
+        assert_eq!(get_links(&model, t, s, a, b), vec![(new, Some(m2)), (second, Some(m1))]);
+
+        assert!(model.delete_scheduled_match(t, s, 100).is_err());
+    }
+}
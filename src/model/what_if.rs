@@ -0,0 +1,124 @@
+use crate::model::Model;
+use crate::model::tournament::{Match, MatchId, Stage, StageId, TournamentId};
+
+// What-if mode lets results be entered to see what would happen (e.g. to the standings), without changing the real data.
+// On entering it, the real tournaments are put aside and everything carries on as normal with a copy of them,
+// but it's always the real tournaments which get saved. The hypothetical changes can then be either discarded or committed.
+// Keeping a whole copy (rather than a separate list of hypothetical results for the Model to read through) means that
+// everything which reads the data (standings, chances, brackets etc.) works the same in what-if mode without knowing about it,
+// and any kind of change can be made hypothetically, not just entering results. What has changed is found by comparing
+// the copy against the real tournaments, including real matches which have been deleted, which still need showing.
+
+impl Model {
+    pub fn is_what_if(&self) -> bool {
+        self.real_tournaments.is_some()
+    }
+
+    /// Starts what-if mode. Until it is left (or committed), any changes are only hypothetical and won't be saved.
+    pub fn enter_what_if(&mut self) -> Result<(), ()> {
+        if self.is_what_if() {
+            return Err(());
+        }
+        self.real_tournaments = Some(self.tournaments.clone());
+        // Nothing has changed yet, but everything should now be shown as being in what-if mode
+        self.changed_tournaments.extend(self.tournaments.keys());
+        // Otherwise undoing could go back past entering what-if mode, mixing real and hypothetical changes
+        self.reset_history();
+        Ok(())
+    }
+
+    /// Leaves what-if mode, throwing away all the hypothetical changes.
+    pub fn leave_what_if(&mut self) -> Result<(), ()> {
+        match self.real_tournaments.take() {
+            Some(real_tournaments) => {
+                // Anything which was added in what-if mode needs updating too, as it will be removed
+                self.changed_tournaments.extend(self.tournaments.keys());
+                self.changed_tournaments.extend(real_tournaments.keys());
+                self.tournaments = real_tournaments;
+                self.reset_history();
+                Ok(())
+            }
+            None => Err(()),
+        }
+    }
+
+    /// Leaves what-if mode, keeping all the hypothetical changes so that they become real.
+    pub fn commit_what_if(&mut self) -> Result<(), ()> {
+        match self.real_tournaments.take() {
+            Some(real_tournaments) => {
+                // Even if nothing was changed, the hypothetical matches no longer need showing as such
+                self.changed_tournaments.extend(self.tournaments.keys());
+                self.changed_tournaments.extend(real_tournaments.keys());
+                Ok(())
+            }
+            None => Err(()),
+        }
+    }
+
+    /// Gets a stage as it really is, ignoring any hypothetical changes. None if not in what-if mode, or if the stage
+    /// only exists in what-if mode.
+    pub fn get_real_stage(&self, tournament_id: TournamentId, stage_id: StageId) -> Option<&Stage> {
+        self.real_tournaments.as_ref()?.get(&tournament_id)?.stages.get(&stage_id)
+    }
+
+    /// Gets the matches in a stage which only exist in what-if mode, or which have a different result there
+    /// than they really do. Empty if not in what-if mode.
+    pub fn get_hypothetical_match_ids(&self, tournament_id: TournamentId, stage_id: StageId) -> Vec<MatchId> {
+        if !self.is_what_if() {
+            return vec![];
+        }
+        let real_stage = self.get_real_stage(tournament_id, stage_id);
+        match self.get_stage(tournament_id, stage_id) {
+            Some(stage) => stage.matches.values()
+                .filter(|m| real_stage.and_then(|s| s.matches.get(&m.id)) != Some(*m))
+                .map(|m| m.id)
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Gets the real matches in a stage which have been deleted in what-if mode, so aren't counted in any of the
+    /// hypothetical results. Empty if not in what-if mode.
+    pub fn get_hypothetically_deleted_matches(&self, tournament_id: TournamentId, stage_id: StageId) -> Vec<&Match> {
+        let stage = self.get_stage(tournament_id, stage_id);
+        match self.get_real_stage(tournament_id, stage_id) {
+            Some(real_stage) => real_stage.matches.values()
+                .filter(|m| stage.is_none_or(|s| !s.matches.contains_key(&m.id)))
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::Model;
+
+    #[test]
+    fn hypothetical_changes() {
+        let mut model = Model::new();
+        let t = model.add_tournament("Test".to_string());
+        let s = model.add_stage_round_robin(t, "Groups".to_string(), 1).unwrap();
+        let [a, b, c] = ["A", "B", "C"].map(|n| model.add_team(t, s, n.to_string()).unwrap());
+        let unchanged = model.add_match(t, s, a, b, 1, 0).unwrap();
+        let changed = model.add_match(t, s, a, c, 1, 0).unwrap();
+        let deleted = model.add_match(t, s, b, c, 1, 0).unwrap();
+        assert!(model.get_hypothetical_match_ids(t, s).is_empty());
+
+        model.enter_what_if().unwrap();
+        model.set_match_score(t, s, changed, 0, 1).unwrap();
+        model.delete_match(t, s, deleted).unwrap();
+        let added = model.add_match(t, s, c, b, 1, 0).unwrap();
+        assert_eq!(model.get_hypothetical_match_ids(t, s), vec![changed, added]);
+        let deleted_matches = model.get_hypothetically_deleted_matches(t, s);
+        assert_eq!(deleted_matches.iter().map(|m| m.id).collect::<Vec<_>>(), vec![deleted]);
+        assert!(!deleted_matches.iter().any(|m| m.id == unchanged));
+
+        // Only the real matches are left afterwards
+        model.leave_what_if().unwrap();
+        let stage = model.get_stage(t, s).unwrap();
+        assert_eq!(stage.matches.keys().copied().collect::<Vec<_>>(), vec![unchanged, changed, deleted]);
+        assert_eq!(stage.matches[&changed].team_a_score, 1);
+        assert!(model.get_hypothetically_deleted_matches(t, s).is_empty());
+    }
+}
@@ -4,8 +4,8 @@ use wasm_bindgen::convert::FromWasmAbi;
 use wasm_bindgen::prelude::Closure;
 
 use match_list::MatchList;
-use web_sys::HtmlElement;
-use crate::model::Model;
+use web_sys::{HtmlElement, window};
+use crate::model::{Model, ModelObserver};
 use outline::Outline;
 use standings::Standings;
 use round_robin_table::RoundRobinTable;
@@ -14,12 +14,18 @@ use crate::with_globals;
 use crate::model::{tournament::{TournamentId}};
 
 use self::bracket_view::BracketView;
+use self::chances::Chances;
+use self::swiss_view::SwissView;
+use self::toolbar::Toolbar;
 
 pub mod round_robin_table;
 pub mod standings;
 pub mod match_list;
 pub mod outline;
 pub mod bracket_view;
+pub mod swiss_view;
+pub mod chances;
+pub mod toolbar;
 
 /// Contains all the UI elements.
 pub struct Ui {
@@ -35,6 +41,9 @@ pub enum UiElement {
     MatchList(MatchList),
     Outline(Outline),
     BracketView(BracketView),
+    SwissView(SwissView),
+    Chances(Chances),
+    Toolbar(Toolbar),
 }
 
 pub enum Event {
@@ -76,14 +85,6 @@ impl Ui {
         self.elements.insert(element.get_id(), element);
     }
 
-    pub fn tournament_changed(&mut self, model: &Model, tournament_id: TournamentId) {
-        let ids: Vec<usize> = self.elements.keys().map(|k| *k).collect();
-
-        for id in ids {
-            self.get_element_mut(id).unwrap().tournament_changed(model, tournament_id);
-        }
-    }
-
     /// Events are deferred until an explicit "pass" where we process them, to avoid passing
     /// around too many mutable references.
     pub fn process_events(&mut self, model: &Model) {
@@ -97,6 +98,16 @@ impl Ui {
     }
 }
 
+impl ModelObserver for Ui {
+    fn tournament_changed(&mut self, model: &Model, tournament_id: TournamentId) {
+        let ids: Vec<usize> = self.elements.keys().map(|k| *k).collect();
+
+        for id in ids {
+            self.get_element_mut(id).unwrap().tournament_changed(model, tournament_id);
+        }
+    }
+}
+
 impl UiElement {
     fn get_id(&self) -> UiElementId {
         match self {
@@ -105,6 +116,9 @@ impl UiElement {
             UiElement::MatchList(x) => x.get_id(),
             UiElement::Outline(x) => x.get_id(),
             UiElement::BracketView(x) => x.get_id(),
+            UiElement::SwissView(x) => x.get_id(),
+            UiElement::Chances(x) => x.get_id(),
+            UiElement::Toolbar(x) => x.get_id(),
         }
     }
 
@@ -115,6 +129,9 @@ impl UiElement {
             UiElement::MatchList(x) => x.get_dom_table(),
             UiElement::Outline(x) => x.get_div(),
             UiElement::BracketView(x) => x.get_dom_root(),
+            UiElement::SwissView(x) => x.get_dom_table(),
+            UiElement::Chances(x) => x.get_dom_table(),
+            UiElement::Toolbar(x) => x.get_div(),
         }
     }
 
@@ -125,6 +142,9 @@ impl UiElement {
             UiElement::MatchList(x) => x.tournament_changed(model, tournament_id),
             UiElement::Outline(x) => x.tournament_changed(model, tournament_id),
             UiElement::BracketView(x) => x.tournament_changed(model, tournament_id),
+            UiElement::SwissView(x) => x.tournament_changed(model, tournament_id),
+            UiElement::Chances(x) => x.tournament_changed(model, tournament_id),
+            UiElement::Toolbar(x) => x.tournament_changed(model, tournament_id),
         }
     }
 
@@ -143,6 +163,8 @@ impl UiElement {
             UiElement::Standings(x) => x.process_events(events, model),
             UiElement::MatchList(x) => x.process_events(events, model),
             UiElement::BracketView(x) => x.process_events(events, model),
+            UiElement::SwissView(x) => x.process_events(events, model),
+            UiElement::Chances(x) => x.process_events(events, model),
             _ => ()
         }
     }
@@ -177,7 +199,18 @@ impl EventList {
 /// It's also responsible for doing post-change updates.
 /// The goal here is to make it easy for UI components to register event callbacks in an ergonomic way,
 /// without having to worry about borrowing of global data etc.
+/// The callback isn't run at all while the Model is locked, so this should be used for anything that might edit the data.
+/// For things that don't (e.g. selecting what to show), use create_view_callback instead so that they still work while locked.
 pub fn create_callback<F: FnMut(&mut Model, &mut Ui) -> () + 'static>(mut f: F) -> Closure<dyn FnMut()> {
+    create_view_callback(move |m, u| {
+        if !m.is_locked() {
+            f(m, u);
+        }
+    })
+}
+
+/// The same as create_callback, but the callback is run even while the Model is locked.
+pub fn create_view_callback<F: FnMut(&mut Model, &mut Ui) -> () + 'static>(mut f: F) -> Closure<dyn FnMut()> {
     Closure::<dyn FnMut()>::new(move || {
         with_globals(|m, u| {
             f(m, u);
@@ -190,10 +223,26 @@ pub fn create_callback<F: FnMut(&mut Model, &mut Ui) -> () + 'static>(mut f: F)
 pub fn create_callback_with_arg<T1: FromWasmAbi + 'static, F: FnMut(&mut Model, &mut Ui, T1) -> () + 'static>(mut f: F) -> Closure<dyn FnMut(T1)> {
     Closure::<dyn FnMut(T1)>::new(move |a1| {
         with_globals(|m, u| {
-            f(m, u, a1);
+            if !m.is_locked() {
+                f(m, u, a1);
+            }
             m.process_updates(u);
             u.process_events(m);
         });
     })
 }
 
+
+/// Asks the user to enter the score of a match, e.g. "2-1". Returns None if they cancel or enter something invalid.
+/// This only checks that the input is a pair of numbers - the Model checks whether the score is allowed in the stage's series format.
+pub fn prompt_for_score(message: &str, default: (u32, u32)) -> Option<(u32, u32)> {
+    let input = window().unwrap().prompt_with_message_and_default(message, &format!("{}-{}", default.0, default.1)).ok()??;
+    let scores: Vec<Option<u32>> = input.split('-').map(|x| x.trim().parse().ok()).collect();
+    match scores[..] {
+        [Some(a), Some(b)] => Some((a, b)),
+        _ => {
+            window().unwrap().alert_with_message(&format!("Invalid score '{input}'. Enter it like '2-1'")).expect("Failed to alert");
+            None
+        }
+    }
+}
@@ -4,9 +4,9 @@ use log::{error};
 use wasm_bindgen::{JsCast};
 use web_sys::{ResizeObserver, HtmlElement, HtmlDivElement, MouseEvent, HtmlButtonElement, DomRect, window, HtmlTemplateElement, Element, HtmlCanvasElement, CanvasRenderingContext2d};
 
-use crate::{dom::{create_element}, model::tournament::{StageId, TournamentId, StageKind, FixtureId, FixtureTeam, Outcome, FixtureInput}, model::Model, ui::{UiElement, UiElementId, create_callback, EventList, Event}};
+use crate::{dom::{create_element}, model::tournament::{StageId, TournamentId, StageKind, FixtureId, FixtureTeam, Outcome, FixtureInput, TeamId}, model::Model, ui::{UiElement, UiElementId, create_callback, EventList, Event}};
 
-use super::create_callback_with_arg;
+use super::{create_callback_with_arg, create_view_callback, prompt_for_score};
 
 pub struct BracketView {
     id: UiElementId,
@@ -15,6 +15,7 @@ pub struct BracketView {
     linked_outline_id: UiElementId,
 
     dom_root: HtmlElement,
+    summary_div: HtmlDivElement, // Lists the teams which have advanced from or been eliminated in the bracket
     canvas_container: HtmlDivElement, // <canvas> can't contain other elements, so we put our fixture divs alongside the canvas in this container
     canvas: HtmlCanvasElement,
     canvas_context: CanvasRenderingContext2d,
@@ -71,6 +72,21 @@ impl BracketView {
         let dom_root = create_element::<HtmlElement>("div");
         dom_root.set_inner_html("<h3>Bracket</h3>");
 
+        // Shortcuts for creating all the fixtures for common formats
+        let single_elimination_button = create_element::<HtmlButtonElement>("button");
+        single_elimination_button.set_inner_text("Generate single elimination");
+        dom_root.append_child(&single_elimination_button).expect("Failed to append child");
+        let double_elimination_button = create_element::<HtmlButtonElement>("button");
+        double_elimination_button.set_inner_text("Generate double elimination");
+        dom_root.append_child(&double_elimination_button).expect("Failed to append child");
+        let gsl_group_button = create_element::<HtmlButtonElement>("button");
+        gsl_group_button.set_inner_text("Generate GSL group");
+        dom_root.append_child(&gsl_group_button).expect("Failed to append child");
+
+        let summary_div = create_element::<HtmlDivElement>("div");
+        summary_div.set_class_name("bracket-view-summary");
+        dom_root.append_child(&summary_div).expect("Failed to append child");
+
         let canvas_container = create_element::<HtmlDivElement>("div");
         canvas_container.set_class_name("bracket-view-canvas-container");
         canvas_container.style().set_property("position", "relative").expect("Failed to set property"); // For children to be absolutely positioned relative to this.
@@ -79,10 +95,34 @@ impl BracketView {
         #[allow(dyn_drop)]
         let mut closures: Vec<Box<dyn Drop>> = vec![];
 
+        let click_closure = Box::new(create_callback(move |model, ui| {
+            if let Some(UiElement::BracketView(this)) = ui.get_element(id) {
+                this.on_generate_single_elimination_click(model);
+            }
+        }));
+        single_elimination_button.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+        closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = Box::new(create_callback(move |model, ui| {
+            if let Some(UiElement::BracketView(this)) = ui.get_element(id) {
+                this.on_generate_double_elimination_click(model);
+            }
+        }));
+        double_elimination_button.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+        closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = Box::new(create_callback(move |model, ui| {
+            if let Some(UiElement::BracketView(this)) = ui.get_element(id) {
+                this.on_generate_gsl_group_click(model);
+            }
+        }));
+        gsl_group_button.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+        closures.push(click_closure); // Needs to be kept alive
+
         let canvas = create_element::<HtmlCanvasElement>("canvas");
         canvas.set_class_name("bracket-view-canvas");
 
-        let canvas_resize_closure = Box::new(create_callback(move |model, ui| {
+        let canvas_resize_closure = Box::new(create_view_callback(move |model, ui| {
             if let Some(UiElement::BracketView(this)) = ui.get_element(id) {
                 this.on_canvas_resize(model);
             }
@@ -120,7 +160,7 @@ impl BracketView {
         closures.push(mouseup_closure); // Needs to be kept alive
 
 
-        let mut result = BracketView { id, tournament_id: None, stage_id: None, linked_outline_id, dom_root, canvas_container, canvas, canvas_context,
+        let mut result = BracketView { id, tournament_id: None, stage_id: None, linked_outline_id, dom_root, summary_div, canvas_container, canvas, canvas_context,
             fixture_divs: HashMap::<FixtureId, HtmlDivElement>::new(), closures,
             current_drag: None, current_connecting_line: None };
 
@@ -149,9 +189,20 @@ impl BracketView {
         //TODO: delete closures?
 
         self.fixture_divs.clear();
+        self.summary_div.set_inner_text("");
 
         if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
             if let Some(stage) = model.get_stage(tournament_id, stage_id) {
+                // Once results have made their way through the bracket, show who has gone out of it (if that's known, e.g. for a GSL group)
+                let mut summary = vec![];
+                for (label, teams) in [("Advanced", stage.get_advanced_teams()), ("Eliminated", stage.get_eliminated_teams())] {
+                    if !teams.is_empty() {
+                        let names: Vec<&str> = teams.iter().filter_map(|t| stage.teams.get(t)).map(|t| &t.name[..]).collect();
+                        summary.push(format!("{label}: {}", names.join(", ")));
+                    }
+                }
+                self.summary_div.set_inner_text(&summary.join("\n"));
+
                 if let StageKind::Bracket { fixtures } = &stage.kind {
                     let template: HtmlTemplateElement = window().unwrap().document().unwrap().get_element_by_id("bracket-view-fixture-template").expect("Failed to find element")
                         .dyn_into().expect("Cast failed");
@@ -173,6 +224,37 @@ impl BracketView {
                         let team_b_span = new_div.query_selector("span[name=team-b]").expect("Missing entry").expect("Missing entry");
                         team_b_span.set_text_content(Some(&format!("{:?}", f.team_b.to_pretty_desc(stage))));
 
+                        // Show the result if the fixture has been played, marking the winner and loser
+                        let team_a_score_cell: HtmlElement = new_div.query_selector("td[name=team-a-score]").expect("Missing entry").expect("Missing entry").dyn_into().expect("Cast failed");
+                        let team_b_score_cell: HtmlElement = new_div.query_selector("td[name=team-b-score]").expect("Missing entry").expect("Missing entry").dyn_into().expect("Cast failed");
+                        let team_a_row = new_div.query_selector("tr[name=team-a-row]").expect("Missing entry").expect("Missing entry");
+                        let team_b_row = new_div.query_selector("tr[name=team-b-row]").expect("Missing entry").expect("Missing entry");
+                        let team_a = stage.get_fixture_team(&f.team_a);
+                        let team_b = stage.get_fixture_team(&f.team_b);
+                        match f.match_id.and_then(|m| stage.matches.get(&m)) {
+                            Some(m) => {
+                                // The match might have its teams the other way round to the fixture
+                                let swapped = Some(m.team_b) == team_a || Some(m.team_a) == team_b;
+                                let (team_a_score, team_b_score) = if swapped { (m.team_b_score, m.team_a_score) } else { (m.team_a_score, m.team_b_score) };
+                                team_a_score_cell.set_inner_text(&team_a_score.to_string());
+                                team_b_score_cell.set_inner_text(&team_b_score.to_string());
+                                // Nobody is marked if it was a draw, or if the fixture's teams can't be worked out any more
+                                let (team_a_class, team_b_class) = match m.get_winner() {
+                                    Some(w) if Some(w) == team_a => ("fixture-winner", "fixture-loser"),
+                                    Some(w) if Some(w) == team_b => ("fixture-loser", "fixture-winner"),
+                                    _ => ("", ""),
+                                };
+                                team_a_row.set_class_name(team_a_class);
+                                team_b_row.set_class_name(team_b_class);
+                            }
+                            None => {
+                                team_a_score_cell.set_inner_text("-");
+                                team_b_score_cell.set_inner_text("-");
+                            }
+                        }
+                        team_a_score_cell.set_title("Click to pick this team as the winner");
+                        team_b_score_cell.set_title("Click to pick this team as the winner");
+
                         new_div.style().set_property("position", "absolute").expect("Failed to set property");
                         new_div.style().set_property("left", &f.layout.0.to_string()).expect("Failed to set property");
                         new_div.style().set_property("top", &f.layout.1.to_string()).expect("Failed to set property");
@@ -188,6 +270,31 @@ impl BracketView {
                         delete_button.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
                         self.closures.push(click_closure); // Needs to be kept alive
 
+                        let result_button: HtmlButtonElement = new_div.query_selector("button[name=result-button]").expect("Missing entry").expect("Missing entry").dyn_into().expect("Cast failed");
+                        let click_closure = Box::new(create_callback(move |model, ui| {
+                            if let Some(UiElement::BracketView(this)) = ui.get_element(id) {
+                                this.on_result_button_click(model, fid);
+                            }
+                        }));
+                        result_button.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+                        self.closures.push(click_closure); // Needs to be kept alive
+
+                        let click_closure = Box::new(create_callback(move |model, ui| {
+                            if let Some(UiElement::BracketView(this)) = ui.get_element(id) {
+                                this.on_pick_winner_click(model, fid, FixtureInput::TeamA);
+                            }
+                        }));
+                        team_a_score_cell.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+                        self.closures.push(click_closure); // Needs to be kept alive
+
+                        let click_closure = Box::new(create_callback(move |model, ui| {
+                            if let Some(UiElement::BracketView(this)) = ui.get_element(id) {
+                                this.on_pick_winner_click(model, fid, FixtureInput::TeamB);
+                            }
+                        }));
+                        team_b_score_cell.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+                        self.closures.push(click_closure); // Needs to be kept alive
+
                         let drag_handle: HtmlElement = new_div.query_selector("span[name=drag-handle]").expect("Missing entry").expect("Missing entry").dyn_into().expect("Cast failed");
                         let mousedown_closure = Box::new(create_callback_with_arg(move |model, ui, e| {
                             if let Some(UiElement::BracketView(this)) = ui.get_element_mut(id) {
@@ -380,14 +487,110 @@ impl BracketView {
         }
     }
 
+    fn on_generate_single_elimination_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Some(seeded_teams) = self.prompt_for_seeding(model) {
+                let third_place_match = seeded_teams.len() >= 4 && window().unwrap().confirm_with_message("Include a third place match?") == Ok(true);
+                if let Err(_) = model.generate_single_elimination(tournament_id, stage_id, &seeded_teams, third_place_match) {
+                    window().unwrap().alert_with_message("Failed to generate the bracket. The bracket must be empty, with at least two different teams.").expect("Failed to alert");
+                }
+            }
+        }
+    }
+
+    fn on_generate_double_elimination_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Some(seeded_teams) = self.prompt_for_seeding(model) {
+                let bracket_reset = window().unwrap().confirm_with_message("Include a bracket reset, in case the lower bracket team wins the grand final?") == Ok(true);
+                if let Err(_) = model.generate_double_elimination(tournament_id, stage_id, &seeded_teams, bracket_reset) {
+                    window().unwrap().alert_with_message("Failed to generate the bracket. The bracket must be empty, with at least three different teams.").expect("Failed to alert");
+                }
+            }
+        }
+    }
+
+    fn on_generate_gsl_group_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Some(seeded_teams) = self.prompt_for_seeding(model) {
+                if let Err(_) = model.generate_gsl_group(tournament_id, stage_id, &seeded_teams) {
+                    window().unwrap().alert_with_message("Failed to generate the group. The bracket must be empty, with exactly four different teams.").expect("Failed to alert");
+                }
+            }
+        }
+    }
+
+    /// Asks the user for the teams to put in a generated bracket, best seed first. Defaults to all the teams in the stage.
+    fn prompt_for_seeding(&self, model: &Model) -> Option<Vec<TeamId>> {
+        let stage = model.get_stage(self.tournament_id?, self.stage_id?)?;
+        let all_teams = stage.teams.values().map(|t| &t.name[..]).collect::<Vec<&str>>().join(", ");
+        let input = window().unwrap().prompt_with_message_and_default("Enter the teams in seed order (best first), separated by commas:", &all_teams).ok()??;
+        let mut result = vec![];
+        for name in input.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()) {
+            match stage.teams.values().find(|t| t.name == name) {
+                Some(t) => result.push(t.id),
+                None => {
+                    window().unwrap().alert_with_message(&format!("Unknown team '{name}'")).expect("Failed to alert");
+                    return None;
+                }
+            }
+        }
+        Some(result)
+    }
+
     fn on_delete_fixture_button_click(&self, model: &mut Model, fixture_id: FixtureId) {
         if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
             if window().unwrap().confirm_with_message(&format!("Are you sure you want to delete this fixture?")) == Ok(true) {
                 //TODO: also delete the associated match if any??
                 //TODO: delete any teams as fixed inputs?
                 if let Err(_) = model.delete_fixture(tournament_id, stage_id, fixture_id) {
-                    error!("Failed to delete fixture");
+                    window().unwrap().alert_with_message("Failed to delete fixture. If other fixtures take their teams from this one, change them first.").expect("Failed to alert");
+                }
+            }
+        }
+    }
+
+    fn on_result_button_click(&self, model: &mut Model, fixture_id: FixtureId) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let (message, current_score, series_format) = match model.get_stage(tournament_id, stage_id) {
+                Some(stage) => match &stage.kind {
+                    StageKind::Bracket { fixtures } => match fixtures.get(&fixture_id) {
+                        Some(f) => {
+                            let team_a = stage.get_fixture_team(&f.team_a);
+                            let current_score = f.match_id.and_then(|m| stage.matches.get(&m))
+                                .map(|m| if Some(m.team_a) == team_a { (m.team_a_score, m.team_b_score) } else { (m.team_b_score, m.team_a_score) })
+                                .unwrap_or(stage.series_format.get_sweep_score());
+                            (format!("Enter the score for {} vs {} ({}):", f.team_a.to_pretty_desc(stage), f.team_b.to_pretty_desc(stage), stage.series_format.get_name()),
+                                current_score, stage.series_format)
+                        }
+                        None => return,
+                    }
+                    _ => return,
                 }
+                None => return,
+            };
+            if let Some((team_a_score, team_b_score)) = prompt_for_score(&message, current_score) {
+                if let Err(_) = model.set_fixture_score(tournament_id, stage_id, fixture_id, team_a_score, team_b_score) {
+                    window().unwrap().alert_with_message(&format!("Couldn't record the result. Both teams need to be known, and the score needs to be valid for a {} match (and not a draw).",
+                        series_format.get_name())).expect("Failed to alert");
+                }
+            }
+        }
+    }
+
+    /// A quick way of entering a result, without having to type in the score. The winner is given a clean sweep,
+    /// which can then be corrected with the result button if needed.
+    fn on_pick_winner_click(&self, model: &mut Model, fixture_id: FixtureId, winner: FixtureInput) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let (winner_score, loser_score) = match model.get_stage(tournament_id, stage_id) {
+                Some(s) => s.series_format.get_sweep_score(),
+                None => return,
+            };
+            let (team_a_score, team_b_score) = match winner {
+                FixtureInput::TeamA => (winner_score, loser_score),
+                FixtureInput::TeamB => (loser_score, winner_score),
+            };
+            if let Err(_) = model.set_fixture_score(tournament_id, stage_id, fixture_id, team_a_score, team_b_score) {
+                window().unwrap().alert_with_message("Couldn't record the result. Both teams need to be known.").expect("Failed to alert");
             }
         }
     }
@@ -0,0 +1,153 @@
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlTableElement, HtmlTableRowElement, HtmlTableSectionElement, HtmlButtonElement, HtmlElement, window};
+
+use crate::{dom::{create_element}, model::tournament::{StageId, TournamentId, StageKind}, model::{Model, simulation::format_chance, standings::to_ordinal}, ui::{create_callback, UiElementId, UiElement, EventList, Event}};
+
+/// Shows how likely each team in a round robin is to finish in each position, based on the matches still to be played.
+pub struct Chances {
+    id: UiElementId,
+    tournament_id: Option<TournamentId>,
+    stage_id: Option<StageId>,
+    linked_outline_id: UiElementId,
+
+    dom_table: HtmlTableElement,
+    headings_row: HtmlTableRowElement,
+    body: HtmlTableSectionElement,
+    summary_cell: HtmlElement,
+
+    closures: Vec<Closure::<dyn FnMut()>>,
+}
+
+impl Chances {
+    pub fn get_id(&self) -> UiElementId {
+        self.id
+    }
+
+    pub fn tournament_changed(&mut self, model: &Model, tournament_id: TournamentId) {
+        if Some(tournament_id) == self.tournament_id {
+            self.refresh(model);
+        }
+    }
+
+    pub fn process_events(&mut self, events: &EventList, model: &Model) {
+        for e in events.get_events() {
+            match e {
+                Event::SelectedTournamentAndStageChanged { source, new_tournament_id, new_stage_id } if *source == self.linked_outline_id => {
+                    self.tournament_id = *new_tournament_id;
+                    self.stage_id = *new_stage_id;
+                    self.refresh(model);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    pub fn get_dom_table(&self) -> &HtmlTableElement {
+        &self.dom_table
+    }
+
+    pub fn new(id: UiElementId, model: &Model, linked_outline_id: UiElementId) -> Chances {
+        let dom_table = create_element::<HtmlTableElement>("table");
+        dom_table.set_class_name("chances");
+
+        let head: HtmlTableSectionElement = dom_table.create_t_head().dyn_into().expect("Cast failed");
+        let head_row: HtmlTableRowElement = head.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        head_row.set_inner_html(r#"<th colspan="3"><h3>Chances</h3></th>"#);
+
+        // The headings depend on the number of teams, so are filled in when refreshing
+        let headings_row: HtmlTableRowElement = head.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+
+        let body: HtmlTableSectionElement = dom_table.create_t_body().dyn_into().expect("Cast failed");
+
+        let foot: HtmlTableSectionElement = dom_table.create_t_foot().dyn_into().expect("Cast failed");
+        let summary_row: HtmlTableRowElement = foot.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        let summary_cell = summary_row.insert_cell().expect("Failed to insert cell");
+        let foot_row: HtmlTableRowElement = foot.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        let cell = foot_row.insert_cell().expect("Failed to insert cell");
+        cell.set_attribute("colspan", "3").expect("Failed to set attribute");
+        let advancing_teams_button: HtmlButtonElement = create_element("button");
+        advancing_teams_button.set_inner_text("Advancing teams");
+        cell.append_child(&advancing_teams_button).expect("Failed to append button");
+
+        let mut result = Chances { id, tournament_id: None, stage_id: None, linked_outline_id, dom_table, headings_row, body, summary_cell, closures: vec![] };
+
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::Chances(this)) = ui.get_element(id) {
+                this.on_advancing_teams_button_click(model);
+            }
+        });
+        advancing_teams_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        result.refresh(model);
+
+        result
+    }
+
+    fn refresh(&mut self, model: &Model) {
+        let mut show = false;
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Some(s) = model.get_stage(tournament_id, stage_id) {
+                if let StageKind::RoundRobin { .. } = s.kind {
+                    show = true;
+                }
+            }
+        }
+        self.dom_table.style().set_property("display",
+            if show { "block" } else { "none" }).expect("Failed to set style");
+
+        while self.body.rows().length() > 0 {
+            self.body.delete_row(0).expect("Failed to delete row");
+        }
+
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Some(stage) = model.get_stage(tournament_id, stage_id) {
+                if let Some(simulation) = model.get_simulation(tournament_id, stage_id) {
+                    let advancing_teams = std::cmp::min(stage.advancing_teams as usize, stage.teams.len());
+                    let top_heading = if advancing_teams > 0 { format!("<th>Top {advancing_teams}</th>") } else { String::new() };
+                    let position_headings: String = (1..=stage.teams.len()).map(|p| format!("<th>{}</th>", to_ordinal(p))).collect();
+                    self.headings_row.set_inner_html(&format!("<th>Team</th>{top_heading}{position_headings}"));
+
+                    for t in &simulation.teams {
+                        let row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+                        let cell = row.insert_cell().expect("Failed to insert cell");
+                        cell.set_inner_text(stage.teams.get(&t.team_id).map(|t| &t.name[..]).unwrap_or("?"));
+                        if advancing_teams > 0 {
+                            let cell = row.insert_cell().expect("Failed to insert cell");
+                            cell.set_inner_text(&format_chance(t.get_top_chance(advancing_teams)));
+                            cell.set_class_name("top-chance");
+                        }
+                        for c in &t.position_chances {
+                            let cell = row.insert_cell().expect("Failed to insert cell");
+                            cell.set_inner_text(&format_chance(*c));
+                        }
+                    }
+
+                    self.summary_cell.set_attribute("colspan", &(stage.teams.len() + 2).to_string()).expect("Failed to set attribute");
+                    self.summary_cell.set_inner_text(&format!("Based on {} remaining matches, with every score equally likely ({})", simulation.remaining_matches.len(),
+                        if simulation.exhaustive { format!("all {} possible outcomes", simulation.num_outcomes) } else { format!("{} random outcomes", simulation.num_outcomes) }));
+                }
+            }
+        }
+    }
+
+    fn on_advancing_teams_button_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let current = match model.get_stage(tournament_id, stage_id) {
+                Some(s) => s.advancing_teams,
+                None => return,
+            };
+            if let Ok(Some(input)) = window().unwrap().prompt_with_message_and_default(
+                "Enter how many teams at the top of the standings go through (e.g. to the playoffs), or 0 for none:", &current.to_string()) {
+                match input.trim().parse() {
+                    Ok(n) => {
+                        if let Err(_) = model.set_stage_advancing_teams(tournament_id, stage_id, n) {
+                            window().unwrap().alert_with_message("That's more than the number of teams").expect("Failed to alert");
+                        }
+                    }
+                    Err(_) => window().unwrap().alert_with_message(&format!("Invalid number '{input}'")).expect("Failed to alert"),
+                }
+            }
+        }
+    }
+}
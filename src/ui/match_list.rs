@@ -2,7 +2,7 @@ use log::error;
 use wasm_bindgen::{JsCast, prelude::Closure};
 use web_sys::{HtmlTableElement, HtmlTableRowElement, HtmlTableSectionElement, HtmlButtonElement, window};
 
-use crate::{dom::{create_element}, model::tournament::{StageId, TournamentId, Stage, Match, MatchId}, model::Model, ui::{create_callback, UiElementId, UiElement, EventList, Event}};
+use crate::{dom::{create_element, create_html_element}, model::tournament::{StageId, TournamentId, Stage, StageKind, Match, MatchId, SeriesFormat, ScheduledMatch}, model::Model, ui::{create_callback, prompt_for_score, UiElementId, UiElement, EventList, Event}};
 
 //TODO: swap blue/red side for a match
 
@@ -14,6 +14,8 @@ pub struct MatchList {
 
     dom_table: HtmlTableElement,
     body: HtmlTableSectionElement,
+    series_format_button: HtmlButtonElement,
+    generate_schedule_button: HtmlButtonElement,
 
     closures: Vec<Closure::<dyn FnMut()>>,
 }
@@ -51,12 +53,50 @@ impl MatchList {
 
         let head: HtmlTableSectionElement = dom_table.create_t_head().dyn_into().expect("Cast failed");
         let head_row: HtmlTableRowElement = head.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
-        head_row.set_inner_html(r#"<th colspan="3"><h3>Matches</h3></th>"#);
+        head_row.set_inner_html(r#"<th colspan="6"><h3>Matches</h3></th>"#);
 
 
         let body: HtmlTableSectionElement = dom_table.create_t_body().dyn_into().expect("Cast failed");
 
-        let mut result = MatchList { id, tournament_id: None, stage_id: None, linked_outline_id, dom_table, body, closures: vec![] };
+        let foot: HtmlTableSectionElement = dom_table.create_t_foot().dyn_into().expect("Cast failed");
+        let foot_row: HtmlTableRowElement = foot.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        let cell = foot_row.insert_cell().expect("Failed to insert cell");
+        cell.set_attribute("colspan", "6").expect("Failed to set attribute");
+        let series_format_button: HtmlButtonElement = create_element("button");
+        cell.append_child(&series_format_button).expect("Failed to append button");
+        let generate_schedule_button: HtmlButtonElement = create_element("button");
+        generate_schedule_button.set_inner_text("Generate schedule");
+        cell.append_child(&generate_schedule_button).expect("Failed to append button");
+        let schedule_match_button: HtmlButtonElement = create_element("button");
+        schedule_match_button.set_inner_text("Schedule match");
+        cell.append_child(&schedule_match_button).expect("Failed to append button");
+
+        let mut result = MatchList { id, tournament_id: None, stage_id: None, linked_outline_id, dom_table, body, series_format_button, generate_schedule_button,
+            closures: vec![] };
+
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
+                this.on_series_format_button_click(model);
+            }
+        });
+        result.series_format_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
+                this.on_generate_schedule_button_click(model);
+            }
+        });
+        result.generate_schedule_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
+                this.on_schedule_match_button_click(model);
+            }
+        });
+        schedule_match_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
 
         result.refresh(model);
 
@@ -74,16 +114,67 @@ impl MatchList {
 
         if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
             if let Some(stage) = model.get_stage(tournament_id, stage_id) {
-                for (idx, (_match_id, m)) in stage.matches.iter().enumerate() {
-                    self.add_match_elements(m, idx, stage);
+                self.series_format_button.set_inner_text(&format!("Series format: {}", stage.series_format.get_name()));
+
+                // Tiebreaker matches are shown separately after the rest, as they don't count towards the teams' records
+                let (tiebreakers, regular): (Vec<_>, Vec<_>) =
+                    stage.matches.values().enumerate().partition(|(_, m)| m.is_tiebreaker);
+                let hypothetical = model.get_hypothetical_match_ids(tournament_id, stage_id);
+                self.add_section(&regular, &hypothetical, stage);
+                if !tiebreakers.is_empty() {
+                    let heading_row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+                    heading_row.set_inner_html(r#"<th colspan="6">Tiebreakers</th>"#);
+                    self.add_section(&tiebreakers, &hypothetical, stage);
                 }
+
+                // Real matches which have been deleted in what-if mode aren't counted, but need showing so that it's clear what's changed
+                let deleted = model.get_hypothetically_deleted_matches(tournament_id, stage_id);
+                if !deleted.is_empty() {
+                    let heading_row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+                    heading_row.set_inner_html(r#"<th colspan="6">Deleted in what-if mode</th>"#);
+                    // The teams might have been deleted too
+                    let real_stage = model.get_real_stage(tournament_id, stage_id).unwrap_or(stage);
+                    for m in deleted {
+                        self.add_deleted_match_elements(m, real_stage);
+                    }
+                }
+
+                // Scheduled matches which haven't been played yet, grouped by round
+                let schedule = match &stage.kind {
+                    StageKind::RoundRobin { schedule, .. } => &schedule[..],
+                    _ => &[],
+                };
+                let mut prev_round = None;
+                for (i, m) in schedule.iter().enumerate().filter(|(_, m)| m.match_id.is_none()) {
+                    if prev_round != Some(m.round) {
+                        self.add_round_heading(m.round, stage);
+                        prev_round = Some(m.round);
+                    }
+                    self.add_scheduled_match_elements(i, m, stage);
+                }
+                self.generate_schedule_button.style().set_property("display",
+                    if matches!(&stage.kind, StageKind::RoundRobin { schedule, .. } if schedule.is_empty()) { "inline" } else { "none" }).expect("Failed to set style");
             }
         }
     }
 
-    fn add_match_elements(&mut self, m: &Match, idx: usize, stage: &Stage) {
+    /// Adds rows for the given matches, which are paired with their index in the stage's full list of matches.
+    /// The matches can only be moved up/down within the section. Any hypothetical matches (from what-if mode) are shown differently.
+    fn add_section(&mut self, matches: &[(usize, &Match)], hypothetical: &[MatchId], stage: &Stage) {
+        for (i, (_, m)) in matches.iter().enumerate() {
+            let prev_idx = if i > 0 { Some(matches[i - 1].0) } else { None };
+            let next_idx = matches.get(i + 1).map(|(idx, _)| *idx);
+            self.add_match_elements(m, prev_idx, next_idx, hypothetical.contains(&m.id), stage);
+        }
+    }
+
+    fn add_match_elements(&mut self, m: &Match, prev_idx: Option<usize>, next_idx: Option<usize>, is_hypothetical: bool, stage: &Stage) {
         // Add row at the end
         let new_row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        if is_hypothetical {
+            new_row.set_class_name("hypothetical");
+            new_row.set_title("Only in what-if mode");
+        }
 
         let cell = new_row.insert_cell().expect("Failed to insert cell");
         let delete_button: HtmlButtonElement = create_element("button");
@@ -101,7 +192,7 @@ impl MatchList {
 
         //TODO: drag and drop
         let cell = new_row.insert_cell().expect("Failed to insert cell");
-        if idx > 0 {
+        if let Some(prev_idx) = prev_idx {
             let move_up_button: HtmlButtonElement = create_element("button");
             move_up_button.set_inner_text("^");
             cell.append_child(&move_up_button).expect("Failed to append button");
@@ -109,7 +200,7 @@ impl MatchList {
             let m_id = m.id;
             let click_closure = create_callback(move |model, ui| {
                 if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
-                    this.on_reorder_match_button_click(model, m_id, idx - 1);
+                    this.on_reorder_match_button_click(model, m_id, prev_idx);
                 }
             });
             move_up_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
@@ -117,7 +208,7 @@ impl MatchList {
         }
 
         let cell = new_row.insert_cell().expect("Failed to insert cell");
-        if idx < stage.matches.len() - 1 {
+        if let Some(next_idx) = next_idx {
             let move_down_button: HtmlButtonElement = create_element("button");
             move_down_button.set_inner_text("v");
             cell.append_child(&move_down_button).expect("Failed to append button");
@@ -125,7 +216,7 @@ impl MatchList {
             let m_id = m.id;
             let click_closure = create_callback(move |model, ui| {
                 if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
-                    this.on_reorder_match_button_click(model, m_id, idx + 1);
+                    this.on_reorder_match_button_click(model, m_id, next_idx);
                 }
             });
             move_down_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
@@ -138,6 +229,128 @@ impl MatchList {
         let team_b = stage.teams.get(&m.team_b).map(|t| &t.name[..]).unwrap_or("?");
         let team_b_score = m.team_b_score;
         cell.set_inner_text(&format!("{team_a} {team_a_score} - {team_b_score} {team_b}"));
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        let edit_score_button: HtmlButtonElement = create_element("button");
+        edit_score_button.set_inner_text("Edit score");
+        cell.append_child(&edit_score_button).expect("Failed to append button");
+        let id = self.id;
+        let m_id = m.id;
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
+                this.on_edit_score_button_click(model, m_id);
+            }
+        });
+        edit_score_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        self.closures.push(click_closure); // Needs to be kept alive
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        let tiebreaker_button: HtmlButtonElement = create_element("button");
+        tiebreaker_button.set_inner_text(if m.is_tiebreaker { "Not tiebreaker" } else { "Tiebreaker" });
+        tiebreaker_button.set_title(if m.is_tiebreaker { "Count this match towards the teams' records" } else { "Mark this match as a tiebreaker, played to separate tied teams" });
+        cell.append_child(&tiebreaker_button).expect("Failed to append button");
+        let id = self.id;
+        let m_id = m.id;
+        let is_tiebreaker = !m.is_tiebreaker;
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
+                this.on_tiebreaker_button_click(model, m_id, is_tiebreaker);
+            }
+        });
+        tiebreaker_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        self.closures.push(click_closure); // Needs to be kept alive
+    }
+
+    fn add_deleted_match_elements(&mut self, m: &Match, stage: &Stage) {
+        let new_row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        new_row.set_class_name("hypothetical deleted");
+        new_row.set_title("Deleted in what-if mode, so not counted (but it's still a real match)");
+
+        // It's no longer in the stage, so there's nothing to do with it apart from leaving what-if mode
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        cell.set_attribute("colspan", "3").expect("Failed to set attribute");
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        cell.set_attribute("colspan", "3").expect("Failed to set attribute");
+        let team_a = stage.teams.get(&m.team_a).map(|t| &t.name[..]).unwrap_or("?");
+        let team_b = stage.teams.get(&m.team_b).map(|t| &t.name[..]).unwrap_or("?");
+        cell.set_inner_text(&format!("{team_a} {} - {} {team_b}", m.team_a_score, m.team_b_score));
+    }
+
+    fn add_round_heading(&mut self, round: u32, stage: &Stage) {
+        let heading_row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        let cell = create_html_element("th");
+        cell.set_attribute("colspan", "6").expect("Failed to set attribute");
+        cell.set_inner_text(&format!("Upcoming - {} ", stage.get_round_label(round)));
+        heading_row.append_child(&cell).expect("Failed to append cell");
+
+        let rename_button: HtmlButtonElement = create_element("button");
+        rename_button.set_inner_text("Rename");
+        cell.append_child(&rename_button).expect("Failed to append button");
+        let id = self.id;
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
+                this.on_rename_round_button_click(model, round);
+            }
+        });
+        rename_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        self.closures.push(click_closure); // Needs to be kept alive
+    }
+
+    fn add_scheduled_match_elements(&mut self, index: usize, m: &ScheduledMatch, stage: &Stage) {
+        let new_row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        new_row.set_class_name("scheduled-match");
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        let delete_button: HtmlButtonElement = create_element("button");
+        delete_button.set_inner_text("X");
+        cell.append_child(&delete_button).expect("Failed to append button");
+        let id = self.id;
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
+                this.on_delete_scheduled_match_button_click(model, index);
+            }
+        });
+        delete_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        self.closures.push(click_closure); // Needs to be kept alive
+
+        // Scheduled matches stay in the order of the schedule, so there's nothing to move up/down
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        cell.set_attribute("colspan", "2").expect("Failed to set attribute");
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        let team_a = stage.teams.get(&m.team_a).map(|t| &t.name[..]).unwrap_or("?");
+        let team_b = stage.teams.get(&m.team_b).map(|t| &t.name[..]).unwrap_or("?");
+        match &m.time {
+            Some(time) => cell.set_inner_text(&format!("{team_a} vs {team_b} ({time})")),
+            None => cell.set_inner_text(&format!("{team_a} vs {team_b}")),
+        }
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        let result_button: HtmlButtonElement = create_element("button");
+        result_button.set_inner_text("Enter result");
+        cell.append_child(&result_button).expect("Failed to append button");
+        let id = self.id;
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
+                this.on_scheduled_match_result_button_click(model, index);
+            }
+        });
+        result_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        self.closures.push(click_closure); // Needs to be kept alive
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        let time_button: HtmlButtonElement = create_element("button");
+        time_button.set_inner_text("Set time");
+        cell.append_child(&time_button).expect("Failed to append button");
+        let id = self.id;
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::MatchList(this)) = ui.get_element(id) {
+                this.on_set_time_button_click(model, index);
+            }
+        });
+        time_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        self.closures.push(click_closure); // Needs to be kept alive
     }
 
     fn on_delete_match_button_click(&self, model: &mut Model, match_id: MatchId) {
@@ -150,6 +363,148 @@ impl MatchList {
         }
     }
 
+    fn on_edit_score_button_click(&self, model: &mut Model, match_id: MatchId) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let (message, current_score, series_format) = match model.get_stage(tournament_id, stage_id).and_then(|s| s.matches.get(&match_id).map(|m| (s, m))) {
+                Some((s, m)) => {
+                    let team_a = s.teams.get(&m.team_a).map(|t| &t.name[..]).unwrap_or("?");
+                    let team_b = s.teams.get(&m.team_b).map(|t| &t.name[..]).unwrap_or("?");
+                    (format!("Enter the score for {team_a} vs {team_b} ({}):", s.series_format.get_name()), (m.team_a_score, m.team_b_score), s.series_format)
+                }
+                None => return,
+            };
+            if let Some((team_a_score, team_b_score)) = prompt_for_score(&message, current_score) {
+                if let Err(_) = model.set_match_score(tournament_id, stage_id, match_id, team_a_score, team_b_score) {
+                    window().unwrap().alert_with_message(&format!("{team_a_score}-{team_b_score} isn't a valid score for a {} match", series_format.get_name())).expect("Failed to alert");
+                }
+            }
+        }
+    }
+
+    fn on_scheduled_match_result_button_click(&self, model: &mut Model, index: usize) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let (message, default_score, series_format) = match model.get_stage(tournament_id, stage_id) {
+                Some(s @ Stage { kind: StageKind::RoundRobin { schedule, .. }, .. }) => match schedule.get(index) {
+                    Some(m) => {
+                        let team_a = s.teams.get(&m.team_a).map(|t| &t.name[..]).unwrap_or("?");
+                        let team_b = s.teams.get(&m.team_b).map(|t| &t.name[..]).unwrap_or("?");
+                        (format!("Enter the score for {team_a} vs {team_b} ({}):", s.series_format.get_name()), s.series_format.get_sweep_score(), s.series_format)
+                    }
+                    None => return,
+                }
+                _ => return,
+            };
+            if let Some((team_a_score, team_b_score)) = prompt_for_score(&message, default_score) {
+                if let Err(_) = model.set_scheduled_match_score(tournament_id, stage_id, index, team_a_score, team_b_score) {
+                    window().unwrap().alert_with_message(&format!("{team_a_score}-{team_b_score} isn't a valid score for a {} match", series_format.get_name())).expect("Failed to alert");
+                }
+            }
+        }
+    }
+
+    fn on_delete_scheduled_match_button_click(&self, model: &mut Model, index: usize) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if window().unwrap().confirm_with_message("Are you sure you want to remove this match from the schedule?") == Ok(true) {
+                if let Err(_) = model.delete_scheduled_match(tournament_id, stage_id, index) {
+                    error!("Failed to delete scheduled match");
+                }
+            }
+        }
+    }
+
+    fn on_set_time_button_click(&self, model: &mut Model, index: usize) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let current = match model.get_stage(tournament_id, stage_id).map(|s| &s.kind) {
+                Some(StageKind::RoundRobin { schedule, .. }) => match schedule.get(index) {
+                    Some(m) => m.time.clone().unwrap_or_default(),
+                    None => return,
+                }
+                _ => return,
+            };
+            if let Ok(Some(time)) = window().unwrap().prompt_with_message_and_default("Enter when this match will be played (leave blank for no time):", &current) {
+                let time = if time.trim().is_empty() { None } else { Some(time.trim().to_string()) };
+                if let Err(_) = model.set_scheduled_match_time(tournament_id, stage_id, index, time) {
+                    error!("Failed to set scheduled match time");
+                }
+            }
+        }
+    }
+
+    fn on_rename_round_button_click(&self, model: &mut Model, round: u32) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let current = match model.get_stage(tournament_id, stage_id) {
+                Some(s) => s.get_round_label(round),
+                None => return,
+            };
+            if let Ok(Some(label)) = window().unwrap().prompt_with_message_and_default("Enter a name for this round, e.g. 'Week 1' (leave blank to just number it):", &current) {
+                let label = if label.trim().is_empty() { None } else { Some(label.trim().to_string()) };
+                if let Err(_) = model.set_schedule_round_label(tournament_id, stage_id, round, label) {
+                    error!("Failed to rename round");
+                }
+            }
+        }
+    }
+
+    fn on_schedule_match_button_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let input = match window().unwrap().prompt_with_message("Enter the two teams and the round number to schedule them in, separated by commas (e.g. 'FNC, G2, 3'):") {
+                Ok(Some(input)) => input,
+                _ => return,
+            };
+            let parts: Vec<&str> = input.split(',').map(|p| p.trim()).collect();
+            let teams_and_round = match (&parts[..], model.get_stage(tournament_id, stage_id)) {
+                ([team_a, team_b, round], Some(s)) => {
+                    let find_team = |name: &str| s.teams.values().find(|t| t.name == name).map(|t| t.id);
+                    match (find_team(team_a), find_team(team_b), round.parse::<u32>()) {
+                        (Some(a), Some(b), Ok(round)) if round > 0 => Some((a, b, round - 1)),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            let scheduled = teams_and_round.and_then(|(a, b, round)| model.add_scheduled_match(tournament_id, stage_id, a, b, round));
+            if scheduled.is_none() {
+                window().unwrap().alert_with_message(&format!("Couldn't schedule '{input}'. Enter two different teams from this stage and a round number.")).expect("Failed to alert");
+            }
+        }
+    }
+
+    fn on_generate_schedule_button_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Err(_) = model.generate_round_robin_schedule(tournament_id, stage_id) {
+                window().unwrap().alert_with_message("Couldn't generate a schedule. The stage needs at least two teams.").expect("Failed to alert");
+            }
+        }
+    }
+
+    fn on_series_format_button_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let current = match model.get_stage(tournament_id, stage_id) {
+                Some(s) => s.series_format.get_name(),
+                None => return,
+            };
+            if let Ok(Some(new_format)) = window().unwrap().prompt_with_message_and_default(
+                "Enter the series format for this stage, either 'bo<N>' for best-of-N games (N must be odd) or 'fixed-<N>' for exactly N games, allowing draws:", &current) {
+                match SeriesFormat::from_name(new_format.trim()) {
+                    Some(f) => {
+                        if let Err(_) = model.set_stage_series_format(tournament_id, stage_id, f) {
+                            window().unwrap().alert_with_message("Some existing matches have scores which aren't valid for this format").expect("Failed to alert");
+                        }
+                    }
+                    None => window().unwrap().alert_with_message(&format!("Unknown series format '{new_format}'")).expect("Failed to alert"),
+                }
+            }
+        }
+    }
+
+    fn on_tiebreaker_button_click(&self, model: &mut Model, match_id: MatchId, is_tiebreaker: bool) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Err(_) = model.set_match_tiebreaker(tournament_id, stage_id, match_id, is_tiebreaker) {
+                error!("Failed to set match tiebreaker");
+            }
+        }
+    }
+
     fn on_reorder_match_button_click(&self, model: &mut Model, match_id: MatchId, new_idx: usize) {
         if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
             if let Err(_) = model.reorder_match(tournament_id, stage_id, match_id, new_idx) {
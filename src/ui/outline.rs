@@ -1,8 +1,8 @@
 use log::{error};
 use wasm_bindgen::{JsCast, prelude::Closure};
-use web_sys::{HtmlElement, HtmlSelectElement, HtmlDivElement, HtmlOptionElement, window};
+use web_sys::{HtmlElement, HtmlSelectElement, HtmlDivElement, HtmlOptionElement, window, HtmlInputElement, FileReader, Blob, BlobPropertyBag, Url, HtmlAnchorElement};
 
-use crate::{dom::{create_element, create_html_element}, model::tournament::{StageId, TournamentId}, model::Model, ui::{create_callback, UiElementId, UiElement, EventList, Event}};
+use crate::{dom::{create_element, create_html_element}, model::tournament::{StageId, TournamentId}, model::{Model, export::ImportMode}, ui::{create_callback, create_view_callback, UiElementId, UiElement, EventList, Event}};
 
 //TODO: reorder tournaments and stages
 
@@ -11,6 +11,8 @@ pub struct Outline {
 
     div: HtmlDivElement,
     select: HtmlSelectElement,
+    import_file_input: HtmlInputElement,
+    import_file_reader: FileReader,
 
     selected_tournament_id: Option<TournamentId>,
     selected_stage_id: Option<StageId>,
@@ -52,6 +54,10 @@ impl Outline {
         add_stage_bracket_button.set_inner_text("Add stage (bracket)");
         div.append_child(&add_stage_bracket_button).expect("Failed to append child");
 
+        let add_stage_swiss_button: HtmlElement = create_html_element("button");
+        add_stage_swiss_button.set_inner_text("Add stage (Swiss)");
+        div.append_child(&add_stage_swiss_button).expect("Failed to append child");
+
         let delete_button: HtmlElement = create_html_element("button");
         delete_button.set_inner_text("Delete");
         div.append_child(&delete_button).expect("Failed to append child");
@@ -60,7 +66,28 @@ impl Outline {
         rename_button.set_inner_text("Rename");
         div.append_child(&rename_button).expect("Failed to append child");
 
-        let mut result = Outline { id, div, select,
+        let export_button: HtmlElement = create_html_element("button");
+        export_button.set_inner_text("Export all");
+        div.append_child(&export_button).expect("Failed to append child");
+
+        let export_tournament_button: HtmlElement = create_html_element("button");
+        export_tournament_button.set_inner_text("Export tournament");
+        div.append_child(&export_tournament_button).expect("Failed to append child");
+
+        let import_button: HtmlElement = create_html_element("button");
+        import_button.set_inner_text("Import");
+        div.append_child(&import_button).expect("Failed to append child");
+
+        // The import button opens the file picker of this (hidden) file input
+        let import_file_input = create_element::<HtmlInputElement>("input");
+        import_file_input.set_type("file");
+        import_file_input.set_accept(".json");
+        import_file_input.style().set_property("display", "none").expect("Failed to set style");
+        div.append_child(&import_file_input).expect("Failed to append child");
+
+        let import_file_reader = FileReader::new().expect("Failed to create FileReader");
+
+        let mut result = Outline { id, div, select, import_file_input, import_file_reader,
             selected_tournament_id: None, selected_stage_id: None, selection_change_event_pending: false, closures: vec![] };
 
         let click_closure = create_callback(move |model, ui| {
@@ -87,6 +114,14 @@ impl Outline {
         add_stage_bracket_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
         result.closures.push(click_closure); // Needs to be kept alive
 
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::Outline(this)) = ui.get_element(id) {
+                this.on_add_stage_swiss_button_click(model);
+            }
+        });
+        add_stage_swiss_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
         let click_closure = create_callback(move |model, ui| {
             if let Some(UiElement::Outline(this)) = ui.get_element(id) {
                 this.on_delete_button_click(model);
@@ -103,8 +138,48 @@ impl Outline {
         rename_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
         result.closures.push(click_closure); // Needs to be kept alive
 
-        //TODO: not working properly for keyboard changes - seems to lag behind by one change
+        let click_closure = create_view_callback(move |model, ui| {
+            if let Some(UiElement::Outline(this)) = ui.get_element(id) {
+                this.on_export_button_click(model);
+            }
+        });
+        export_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = create_view_callback(move |model, ui| {
+            if let Some(UiElement::Outline(this)) = ui.get_element(id) {
+                this.on_export_tournament_button_click(model);
+            }
+        });
+        export_tournament_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = create_callback(move |_model, ui| {
+            if let Some(UiElement::Outline(this)) = ui.get_element(id) {
+                this.import_file_input.click();
+            }
+        });
+        import_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
         let change_closure = create_callback(move |_model, ui| {
+            if let Some(UiElement::Outline(this)) = ui.get_element(id) {
+                this.on_import_file_input_change();
+            }
+        });
+        result.import_file_input.set_onchange(Some(change_closure.as_ref().unchecked_ref()));
+        result.closures.push(change_closure); // Needs to be kept alive
+
+        let load_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::Outline(this)) = ui.get_element(id) {
+                this.on_import_file_load(model);
+            }
+        });
+        result.import_file_reader.set_onload(Some(load_closure.as_ref().unchecked_ref()));
+        result.closures.push(load_closure); // Needs to be kept alive
+
+        //TODO: not working properly for keyboard changes - seems to lag behind by one change
+        let change_closure = create_view_callback(move |_model, ui| {
             if let Some(UiElement::Outline(this)) = ui.get_element_mut(id) {
                 this.on_select_change();
             }
@@ -154,7 +229,13 @@ impl Outline {
     fn on_add_stage_round_robin_button_click(&self, model: &mut Model) {
         if let Some(t) = self.selected_tournament_id {
             if let Ok(Some(name)) = window().unwrap().prompt_with_message("Enter name for new stage:") {
-                model.add_stage_round_robin(t, name);
+                if let Ok(Some(legs)) = window().unwrap().prompt_with_message_and_default(
+                    "Enter the number of times each pair of teams plays each other (e.g. 2 for a double round robin):", "1") {
+                    match legs.trim().parse() {
+                        Ok(legs) if legs > 0 => { model.add_stage_round_robin(t, name, legs); },
+                        _ => window().unwrap().alert_with_message(&format!("Invalid number '{legs}'")).expect("Failed to alert"),
+                    }
+                }
             }
         }
     }
@@ -167,6 +248,24 @@ impl Outline {
         }
     }
 
+    fn on_add_stage_swiss_button_click(&self, model: &mut Model) {
+        if let Some(t) = self.selected_tournament_id {
+            if let Ok(Some(name)) = window().unwrap().prompt_with_message("Enter name for new stage:") {
+                if let Ok(Some(settings)) = window().unwrap().prompt_with_message_and_default(
+                    "Enter the number of rounds, the wins needed to advance and the losses that eliminate a team, separated by commas:", "5, 3, 3") {
+                    let numbers: Vec<Option<u32>> = settings.split(',').map(|n| n.trim().parse().ok()).collect();
+                    let stage_id = match numbers[..] {
+                        [Some(rounds), Some(advance_wins), Some(eliminate_losses)] => model.add_stage_swiss(t, name, rounds, advance_wins, eliminate_losses),
+                        _ => None,
+                    };
+                    if stage_id.is_none() {
+                        window().unwrap().alert_with_message(&format!("Invalid settings '{settings}'")).expect("Failed to alert");
+                    }
+                }
+            }
+        }
+    }
+
     //TODO: this should fire when deleting something from the list, resulting in a refresh. However that would then break things
     // whenever we modify a tournament that is currently selected - need to retain selection through a refresh!
     fn on_select_change(&mut self) {
@@ -232,6 +331,61 @@ impl Outline {
         }
     }
 
+    fn on_export_button_click(&self, model: &Model) {
+        let json = match model.export_json() {
+            Ok(j) => j,
+            Err(e) => {
+                error!("Failed to export: {e}");
+                return;
+            }
+        };
+        download_file("tournament-tracker-export.json", &json);
+    }
+
+    fn on_export_tournament_button_click(&self, model: &Model) {
+        if let Some(t) = self.selected_tournament_id {
+            let json = match model.export_tournament_json(t) {
+                Ok(j) => j,
+                Err(e) => {
+                    error!("Failed to export: {e}");
+                    return;
+                }
+            };
+            let tournament_name = model.get_tournament(t).map(|t| t.name.clone()).unwrap_or("tournament".to_string());
+            download_file(&format!("{tournament_name}.json"), &json);
+        }
+    }
+
+    fn on_import_file_input_change(&self) {
+        if let Some(file) = self.import_file_input.files().and_then(|f| f.get(0)) {
+            // The result will be delivered to on_import_file_load
+            self.import_file_reader.read_as_text(&file).expect("Failed to read file");
+        }
+        // Clear the selection, so that choosing the same file again still triggers a change
+        self.import_file_input.set_value("");
+    }
+
+    fn on_import_file_load(&self, model: &mut Model) {
+        let json = match self.import_file_reader.result().ok().and_then(|r| r.as_string()) {
+            Some(j) => j,
+            None => {
+                error!("Failed to read file");
+                return;
+            }
+        };
+
+        let mode = match window().unwrap().prompt_with_message_and_default(
+            "Enter 'merge' to add the imported tournaments alongside the existing ones, or 'replace' to replace ALL existing data:", "merge") {
+            Ok(Some(x)) if x == "merge" => ImportMode::Merge,
+            Ok(Some(x)) if x == "replace" => ImportMode::Replace,
+            _ => return,
+        };
+
+        if let Err(e) = model.import_json(&json, mode) {
+            window().unwrap().alert_with_message(&format!("Failed to import: {e}")).expect("Failed to alert");
+        }
+    }
+
     pub fn get_events(&mut self) -> EventList {
         if self.selection_change_event_pending {
             self.selection_change_event_pending = false;
@@ -241,3 +395,19 @@ impl Outline {
         }
     }
 }
+
+/// Makes the browser download a file with the given contents, as if the user had clicked a link to it.
+fn download_file(filename: &str, contents: &str) {
+    let parts = js_sys::Array::of1(&contents.into());
+    let mut options = BlobPropertyBag::new();
+    options.type_("application/json");
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options).expect("Failed to create blob");
+    let url = Url::create_object_url_with_blob(&blob).expect("Failed to create URL");
+
+    let a = create_element::<HtmlAnchorElement>("a");
+    a.set_href(&url);
+    a.set_download(filename);
+    a.click();
+
+    Url::revoke_object_url(&url).expect("Failed to revoke URL");
+}
@@ -1,14 +1,11 @@
-use indexmap::IndexMap;
 use log::error;
 use wasm_bindgen::{JsCast, prelude::Closure};
 use web_sys::{HtmlTableElement, HtmlTableRowElement, HtmlTableSectionElement};
 
-use crate::{dom::{create_element}, model::tournament::{StageId, TournamentId, Team, Match, TeamId, StageKind}, model::Model, ui::{UiElement, UiElementId, create_callback, EventList, Event}};
+use crate::{dom::{create_element, create_html_element}, model::tournament::{StageId, TournamentId, Team, TeamId, MatchId, StageKind, Stage}, model::Model, ui::{UiElement, UiElementId, create_callback, EventList, Event}};
 
 //TODO: highlight column and row on mouse over? Or altnerate shading to make rows/cols easier to follow
 //TODO: sort by score?
-//TODO: support multiple games between the same teams (e.g. double round robin)
-//TODO: what about tie breaker games after the normal round robins?
 //TODO: make cells uniform width
 
 pub struct RoundRobinTable {
@@ -102,64 +99,115 @@ impl RoundRobinTable {
                 }
 
                 // One row per team
+                let legs = match stage.kind {
+                    StageKind::RoundRobin { legs, .. } => legs,
+                    _ => 1,
+                };
+                let hypothetical = model.get_hypothetical_match_ids(tournament_id, stage_id);
                 for (_team_id, team) in &stage.teams {
-                    self.add_row(team, &stage.teams, &stage.matches);
+                    self.add_row(team, stage, legs, &hypothetical);
                 }
             }
         }
     }
 
-    fn add_row(&mut self, team: &Team, teams: &IndexMap<usize, Team>, matches: &IndexMap<usize, Match>) {
+    fn add_row(&mut self, team: &Team, stage: &Stage, legs: u32, hypothetical: &[MatchId]) {
         // Add row at the end
         let new_row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
         let cell = new_row.insert_cell().expect("Failed to insert cell");
         cell.set_inner_text(&team.name);
 
-        for (&other_team_id, _other_team) in teams {
+        for &other_team_id in stage.teams.keys() {
             let cell = new_row.insert_cell().expect("Failed to insert cell");
 
-            // Check if these teams have played
-            if let Some((_, m)) = matches.iter().find(|(_, m)| m.is_between(team.id, other_team_id)) {
-                cell.set_inner_text(if m.get_winner() == Some(team.id) { "W" } else { "L" });
-            } else if team.id == other_team_id {
+            if team.id == other_team_id {
                 cell.set_inner_text("+"); // make the diagonal distinguished from other matches not yet played (as these can never be played!)
-            } else {
-                cell.set_inner_text("-");
+                continue;
             }
 
-            let id = self.id;
-            let team_id = team.id;
-            let other_team_id = other_team_id;
-            let click_closure = create_callback(move |model, ui| {
-                if let Some(UiElement::RoundRobinTable(this)) = ui.get_element(id) {
-                    this.on_result_click(model, team_id, other_team_id);
+            // One result per leg, e.g. "W/L" for a double round robin. Each can be clicked separately.
+            // Scheduled matches which haven't been played yet come after the played ones.
+            let matches = stage.get_matches_between(team.id, other_team_id);
+            let scheduled = stage.get_unplayed_scheduled_matches_between(team.id, other_team_id);
+            let num_slots = std::cmp::max(legs as usize, matches.len() + scheduled.len());
+            for leg in 0..num_slots {
+                if leg > 0 {
+                    cell.append_with_str_1("/").expect("Failed to append text");
                 }
-            });
-            cell.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
 
-            self.closures.push(click_closure); // Needs to be kept alive
+                let span = create_html_element("span");
+                span.set_inner_text(match matches.get(leg) {
+                    Some(m) if m.get_winner() == Some(team.id) => "W",
+                    Some(m) if m.is_draw() => "D",
+                    Some(_) => "L",
+                    None if leg < matches.len() + scheduled.len() => "*",
+                    None => "-",
+                });
+                if let Some(s) = leg.checked_sub(matches.len()).and_then(|i| scheduled.get(i)) {
+                    span.set_class_name("scheduled-result");
+                    span.set_title(&match &s.time {
+                        Some(time) => format!("Scheduled for {} ({time})", stage.get_round_label(s.round)),
+                        None => format!("Scheduled for {}", stage.get_round_label(s.round)),
+                    });
+                }
+                if matches.get(leg).is_some_and(|m| hypothetical.contains(&m.id)) {
+                    span.set_class_name("hypothetical");
+                    span.set_title("Only in what-if mode");
+                }
+                cell.append_child(&span).expect("Failed to append child");
+
+                // New matches always go in the next leg without a result, so that's the only empty one which can be clicked
+                if leg > matches.len() {
+                    let title = span.title();
+                    span.set_title(&if title.is_empty() { "Enter the earlier legs first".to_string() } else { format!("{title}. Enter the earlier legs first") });
+                    continue;
+                }
+
+                let id = self.id;
+                let team_id = team.id;
+                let click_closure = create_callback(move |model, ui| {
+                    if let Some(UiElement::RoundRobinTable(this)) = ui.get_element(id) {
+                        this.on_result_click(model, team_id, other_team_id, leg);
+                    }
+                });
+                span.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+
+                self.closures.push(click_closure); // Needs to be kept alive
+            }
         }
     }
 
-    fn on_result_click(&self, model: &mut Model, team_id: TeamId, other_team_id: TeamId) {
+    fn on_result_click(&self, model: &mut Model, team_id: TeamId, other_team_id: TeamId, leg: usize) {
         if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
             if let Some(stage) = model.get_stage(tournament_id, stage_id) {
                 //TODO: check for confirmation before changing/removing match results?
 
-                // Check if these teams have played
-                if let Some((match_id, m)) = stage.matches.iter().find(|(_, m)| m.is_between(team_id, other_team_id)) {
+                // Check if these teams have played in this leg. Cycle through win -> loss -> draw (if possible) -> not played.
+                let matches = stage.get_matches_between(team_id, other_team_id);
+                if leg > matches.len() {
+                    // Adding a match would fill in an earlier leg instead
+                    error!("Can't enter leg {} before leg {}", leg + 1, matches.len() + 1);
+                } else if let Some(m) = matches.get(leg) {
+                    let match_id = m.id;
                     if m.get_winner() == Some(team_id) {
-                        if let Err(_) = model.delete_match(tournament_id, stage_id, *match_id) {
-                            error!("Failed to delete match");
+                        // Change the result in place, so that the match stays in the same leg.
+                        // Swapping the scores keeps the same series score (e.g. 2-1 becomes 1-2).
+                        if let Err(_) = model.set_match_score(tournament_id, stage_id, match_id, m.team_b_score, m.team_a_score) {
+                            error!("Failed to update match");
+                        }
+                    } else if let (Some(_), Some((team_a_score, team_b_score))) = (m.get_loser(), stage.series_format.get_draw_score()) {
+                        if let Err(_) = model.set_match_score(tournament_id, stage_id, match_id, team_a_score, team_b_score) {
+                            error!("Failed to update match");
                         }
-                        model.add_match(tournament_id, stage_id, team_id, other_team_id, 0, 1);
                     } else {
-                        if let Err(_) = model.delete_match(tournament_id, stage_id, *match_id) {
+                        if let Err(_) = model.delete_match(tournament_id, stage_id, match_id) {
                             error!("Failed to delete match");
                         }
                     }
                 } else {
-                    model.add_match(tournament_id, stage_id, team_id, other_team_id, 1, 0);
+                    // Assume a clean sweep to start with - the score can be corrected in the match list
+                    let (winner_score, loser_score) = stage.series_format.get_sweep_score();
+                    model.add_match(tournament_id, stage_id, team_id, other_team_id, winner_score, loser_score);
                 }
             }
         }
@@ -2,12 +2,9 @@ use log::{error};
 use wasm_bindgen::{JsCast, prelude::Closure};
 use web_sys::{HtmlTableElement, HtmlTableRowElement, HtmlElement, HtmlTableSectionElement, HtmlButtonElement, window};
 
-use crate::{dom::{create_element, create_html_element}, model::tournament::{StageId, TournamentId, TeamId, Stage, Team}, model::Model, ui::{create_callback, UiElementId, UiElement, Event, EventList}};
+use crate::{dom::{create_element, create_html_element}, model::tournament::{StageId, TournamentId, TeamId, MatchId, Stage, Tiebreaker, ScoringSystem}, model::{Model, clinch::ClinchStatus, standings::StandingsEntry, form::{get_current_streak, get_longest_streak, MatchResult}}, ui::{create_callback, UiElementId, UiElement, Event, EventList}};
 
 
-//TODO: show total games played too
-//TODO: show position (1st, 2nd etc), including ties
-//TODO: show 'trend' e.g. last 5 game score, or full list of 'WLLWLLWL'. Maybe show which teams against too?
 //TODO: rename teams
 
 pub struct Standings {
@@ -18,12 +15,16 @@ pub struct Standings {
 
     dom_table: HtmlTableElement,
     head_row: HtmlTableRowElement,
+    headings_row: HtmlTableRowElement,
     body: HtmlTableSectionElement,
 
     closures: Vec<Closure::<dyn FnMut()>>,
 }
 
 impl Standings {
+    /// How many of the most recent results to show in the form column.
+    const FORM_LENGTH: usize = 5;
+
     pub fn get_id(&self) -> UiElementId {
         self.id
     }
@@ -57,7 +58,10 @@ impl Standings {
 
         let head: HtmlTableSectionElement = dom_table.create_t_head().dyn_into().expect("Cast failed");
         let head_row: HtmlTableRowElement = head.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
-        head_row.set_inner_html(r#"<th colspan="3"><h3>Standings</h3></th>"#);
+        head_row.set_inner_html(r#"<th colspan="13"><h3>Standings</h3></th>"#);
+
+        // The headings depend on the stage, so are filled in when refreshing
+        let headings_row: HtmlTableRowElement = head.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
 
         let body: HtmlTableSectionElement = dom_table.create_t_body().dyn_into().expect("Cast failed");
 
@@ -66,12 +70,29 @@ impl Standings {
         let cell = foot_row.insert_cell().expect("Failed to insert cell");
         cell.set_inner_text("");
         let cell = foot_row.insert_cell().expect("Failed to insert cell");
+        cell.set_inner_text("");
+        let cell = foot_row.insert_cell().expect("Failed to insert cell");
 
         let add_team_button: HtmlElement = create_html_element("button");
         add_team_button.set_inner_text("Add team");
         cell.append_child(&add_team_button).expect("Failed to append child");
 
-        let mut result = Standings { id, tournament_id: None, stage_id: None, linked_outline_id, dom_table, head_row, body, closures: vec![] };
+        let cell = foot_row.insert_cell().expect("Failed to insert cell");
+        let tiebreakers_button: HtmlElement = create_html_element("button");
+        tiebreakers_button.set_inner_text("Tiebreakers");
+        cell.append_child(&tiebreakers_button).expect("Failed to append child");
+
+        let cell = foot_row.insert_cell().expect("Failed to insert cell");
+        let scoring_button: HtmlElement = create_html_element("button");
+        scoring_button.set_inner_text("Scoring");
+        cell.append_child(&scoring_button).expect("Failed to append child");
+
+        let cell = foot_row.insert_cell().expect("Failed to insert cell");
+        let add_tiebreaker_match_button: HtmlElement = create_html_element("button");
+        add_tiebreaker_match_button.set_inner_text("Add tiebreaker match");
+        cell.append_child(&add_tiebreaker_match_button).expect("Failed to append child");
+
+        let mut result = Standings { id, tournament_id: None, stage_id: None, linked_outline_id, dom_table, head_row, headings_row, body, closures: vec![] };
 
         let click_closure = create_callback(move |model, ui| {
             if let Some(UiElement::Standings(this)) = ui.get_element(id) {
@@ -82,6 +103,33 @@ impl Standings {
 
         result.closures.push(click_closure); // Needs to be kept alive
 
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::Standings(this)) = ui.get_element(id) {
+                this.on_tiebreakers_button_click(model);
+            }
+        });
+        tiebreakers_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::Standings(this)) = ui.get_element(id) {
+                this.on_scoring_button_click(model);
+            }
+        });
+        scoring_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::Standings(this)) = ui.get_element(id) {
+                this.on_add_tiebreaker_match_button_click(model);
+            }
+        });
+        add_tiebreaker_match_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+
+        result.closures.push(click_closure); // Needs to be kept alive
+
         result.refresh(model);
 
         result
@@ -107,33 +155,115 @@ impl Standings {
 
         if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
             if let Some(stage) = model.get_stage(tournament_id, stage_id) {
-                // Sort by win/loss score
-                let mut sorted_teams : Vec<&Team> = stage.teams.values().collect();
-                sorted_teams.sort_by_cached_key(|t| {
-                    let w = stage.matches.values().filter(|m| m.get_winner() == Some(t.id)).count();
-                    let l = stage.matches.values().filter(|m| m.get_loser() == Some(t.id)).count();
-                    (w, l)
-                });
-
-                for team in sorted_teams.iter().rev() {
-                    self.add_team_elements(team.id, &team.name, stage);
+                let record_heading = if stage.has_draws() { "W - D - L" } else { "W - L" };
+                let points_heading = if stage.scoring_system.uses_points() { "<th>Pts</th>" } else { "" };
+                self.headings_row.set_inner_html(&format!(r#"<th>Pos</th><th>Team</th><th>{record_heading}</th>{points_heading}<th>GP</th><th>Win %</th><th title="Games won minus games lost">Diff</th><th>Form</th><th>Streak</th><th>Best</th><th></th><th></th><th></th>"#));
+
+                // Alternate the shading of tied groups, so that adjacent groups can be told apart
+                let mut tied_group_count = 0;
+                let mut prev_position = None;
+                // This is shared with the chances, so that the remaining matches only need playing out once
+                let simulation = model.get_simulation(tournament_id, stage_id);
+                let clinch_statuses = simulation.as_ref().map(|s| &s.clinch_statuses[..]).unwrap_or(&[]);
+                let hypothetical = model.get_hypothetical_match_ids(tournament_id, stage_id);
+                for entry in stage.get_standings() {
+                    if entry.tied && prev_position != Some(entry.position) {
+                        tied_group_count += 1;
+                    }
+                    prev_position = Some(entry.position);
+                    let clinch_status = clinch_statuses.iter().find(|c| c.team_id == entry.team_id);
+                    self.add_team_elements(&entry, clinch_status, &hypothetical, stage, tied_group_count % 2 == 0);
                 }
 
             }
         }
     }
 
-    fn add_team_elements(&mut self, team_id: TeamId, team_name: &str, stage: &Stage) {
+    fn add_team_elements(&mut self, entry: &StandingsEntry, clinch_status: Option<&ClinchStatus>, hypothetical: &[MatchId], stage: &Stage, alt_tied_shading: bool) {
+        let team_id = entry.team_id;
+        let team_name = stage.teams.get(&team_id).map(|t| &t.name[..]).unwrap_or("?");
+
         // Add row at the end
         let new_row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        if entry.tied {
+            new_row.set_class_name(if alt_tied_shading { "tied tied-alt" } else { "tied" });
+        }
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        cell.set_inner_text(&entry.get_position_desc());
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        cell.set_inner_text(team_name);
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        if stage.has_draws() {
+            cell.set_inner_text(&format!("{} - {} - {}", entry.wins, entry.draws, entry.losses));
+        } else {
+            cell.set_inner_text(&format!("{} - {}", entry.wins, entry.losses));
+        }
+
+        if stage.scoring_system.uses_points() {
+            let cell = new_row.insert_cell().expect("Failed to insert cell");
+            cell.set_inner_text(&entry.points.to_string());
+        }
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        cell.set_inner_text(&entry.games_played.to_string());
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        cell.set_inner_text(&entry.get_win_rate().map(|r| format!("{:.0}%", r * 100.0)).unwrap_or("-".to_string()));
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        cell.set_inner_text(&format!("{:+}", entry.score_differential));
+
+        // Most recent results, with details of each match on hover
+        let form = stage.get_form(team_id);
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        for f in form.iter().skip(form.len().saturating_sub(Standings::FORM_LENGTH)) {
+            let span = create_html_element("span");
+            span.set_inner_text(&f.result.to_char().to_string());
+            // Results which are only in what-if mode are shown differently
+            if hypothetical.contains(&f.match_id) {
+                span.set_class_name(&format!("form-{} hypothetical", f.result.to_char()));
+            } else {
+                span.set_class_name(&format!("form-{}", f.result.to_char()));
+            }
+            let opponent_name = stage.teams.get(&f.opponent).map(|t| &t.name[..]).unwrap_or("?");
+            let score = stage.matches.get(&f.match_id).map(|m| {
+                if m.team_a == team_id { (m.team_a_score, m.team_b_score) } else { (m.team_b_score, m.team_a_score) }
+            }).unwrap_or((0, 0));
+            span.set_title(&format!("vs {opponent_name} ({} - {})", score.0, score.1));
+            cell.append_child(&span).expect("Failed to append child");
+        }
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        cell.set_inner_text(&get_current_streak(&form).map(|s| s.get_desc()).unwrap_or_default());
 
         let cell = new_row.insert_cell().expect("Failed to insert cell");
-        cell.set_inner_text(&team_name);
+        cell.set_inner_text(&get_longest_streak(&form, MatchResult::Win).map(|s| s.get_desc()).unwrap_or_default());
 
+        // Explain how this team was separated from the one above, if they have the same record
         let cell = new_row.insert_cell().expect("Failed to insert cell");
-        let w = stage.matches.values().filter(|m| m.get_winner() == Some(team_id)).count();
-        let l = stage.matches.values().filter(|m| m.get_loser() == Some(team_id)).count();
-        cell.set_inner_text(&format!("{w} - {l}"));
+        cell.set_inner_text(&entry.separation.and_then(|s| s.get_tiebreak_desc()).unwrap_or_default());
+
+        // What has been decided for this team (round robins only), with what they need to do to clinch on hover
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        if let Some(c) = clinch_status {
+            let span = create_html_element("span");
+            match c.get_badge() {
+                Some(badge) => {
+                    span.set_inner_text(&badge);
+                    span.set_class_name(if c.eliminated { "badge badge-eliminated" } else { "badge badge-clinched" });
+                }
+                None => {
+                    if let (Some(wins), Some(explanation)) = (c.wins_to_clinch, c.get_explanation(team_name)) {
+                        span.set_inner_text(&format!("Magic number: {wins}"));
+                        span.set_title(&explanation);
+                    }
+                }
+            }
+            cell.append_child(&span).expect("Failed to append child");
+        }
 
         let cell = new_row.insert_cell().expect("Failed to insert cell");
         let delete_button: HtmlButtonElement = create_element("button");
@@ -158,6 +288,76 @@ impl Standings {
         }
     }
 
+    fn on_tiebreakers_button_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let current = match model.get_stage(tournament_id, stage_id) {
+                Some(s) => s.tiebreakers.iter().map(|t| t.get_name()).collect::<Vec<&str>>().join(", "),
+                None => return,
+            };
+            let all = Tiebreaker::ALL.iter().map(|t| t.get_name()).collect::<Vec<&str>>().join(", ");
+            if let Ok(Some(new_tiebreakers)) = window().unwrap().prompt_with_message_and_default(
+                &format!("Enter the tiebreakers to use for teams with the same record, in order of priority. Available tiebreakers: {all}"), &current) {
+                let mut tiebreakers = vec![];
+                for name in new_tiebreakers.split(',').map(|n| n.trim()).filter(|n| !n.is_empty()) {
+                    match Tiebreaker::from_name(name) {
+                        Some(t) => tiebreakers.push(t),
+                        None => {
+                            window().unwrap().alert_with_message(&format!("Unknown tiebreaker '{name}'")).expect("Failed to alert");
+                            return;
+                        }
+                    }
+                }
+                if let Err(_) = model.set_stage_tiebreakers(tournament_id, stage_id, tiebreakers) {
+                    error!("Failed to set tiebreakers");
+                }
+            }
+        }
+    }
+
+    fn on_scoring_button_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let current = match model.get_stage(tournament_id, stage_id) {
+                Some(s) => s.scoring_system.get_name(),
+                None => return,
+            };
+            if let Ok(Some(new_scoring)) = window().unwrap().prompt_with_message_and_default(
+                &format!("Enter how teams are ranked: 'win-loss', 'points:<win>/<draw>/<loss>' for points per match result (e.g. '{}') \
+                    or 'games:<points>' for points per game won. Add '+<points>' on the end to give bonus points for a sweep (e.g. 2-0).",
+                    ScoringSystem::THREE_ONE_ZERO.get_name()), &current) {
+                match ScoringSystem::from_name(new_scoring.trim()) {
+                    Some(s) => {
+                        if let Err(_) = model.set_stage_scoring_system(tournament_id, stage_id, s) {
+                            error!("Failed to set scoring system");
+                        }
+                    }
+                    None => window().unwrap().alert_with_message(&format!("Unknown scoring system '{new_scoring}'")).expect("Failed to alert"),
+                }
+            }
+        }
+    }
+
+    fn on_add_tiebreaker_match_button_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Ok(Some(input)) = window().unwrap().prompt_with_message("Enter the winner and loser of the tiebreaker match, separated by a comma (e.g. 'FNC, G2'):") {
+                let find_team = |name: &str| model.get_stage(tournament_id, stage_id)
+                    .and_then(|s| s.teams.values().find(|t| t.name == name.trim()).map(|t| t.id));
+                let names: Vec<&str> = input.split(',').collect();
+                match names[..] {
+                    [winner, loser] => match (find_team(winner), find_team(loser)) {
+                        (Some(winner), Some(loser)) => {
+                            let (winner_score, loser_score) = model.get_stage(tournament_id, stage_id).map(|s| s.series_format.get_sweep_score()).unwrap_or((1, 0));
+                            if model.add_tiebreaker_match(tournament_id, stage_id, winner, loser, winner_score, loser_score).is_none() {
+                                error!("Failed to add tiebreaker match");
+                            }
+                        }
+                        _ => window().unwrap().alert_with_message("Unknown team").expect("Failed to alert"),
+                    }
+                    _ => window().unwrap().alert_with_message("Enter exactly two teams").expect("Failed to alert"),
+                }
+            }
+        }
+    }
+
     fn on_delete_team_button_click(&self, model: &mut Model, team_id: TeamId, team_name: &str) {
         if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
             if window().unwrap().confirm_with_message(&format!("Are you sure you want to delete team '{team_name}'? All data for this team will be lost!!")) == Ok(true) {
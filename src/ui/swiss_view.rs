@@ -0,0 +1,197 @@
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlTableElement, HtmlTableRowElement, HtmlTableSectionElement, HtmlButtonElement, window};
+
+use crate::{dom::{create_element, create_html_element}, model::tournament::{StageId, TournamentId, Stage, StageKind, SwissPairing}, model::swiss::SwissStatus, model::Model, ui::{create_callback, prompt_for_score, UiElementId, UiElement, EventList, Event}};
+
+/// Shows the pairings for each round of a Swiss stage, and lets the results be entered.
+pub struct SwissView {
+    id: UiElementId,
+    tournament_id: Option<TournamentId>,
+    stage_id: Option<StageId>,
+    linked_outline_id: UiElementId,
+
+    dom_table: HtmlTableElement,
+    body: HtmlTableSectionElement,
+    generate_round_button: HtmlButtonElement,
+
+    closures: Vec<Closure::<dyn FnMut()>>,
+}
+
+impl SwissView {
+    pub fn get_id(&self) -> UiElementId {
+        self.id
+    }
+
+    pub fn tournament_changed(&mut self, model: &Model, tournament_id: TournamentId) {
+        if Some(tournament_id) == self.tournament_id {
+            self.refresh(model);
+        }
+    }
+
+    pub fn process_events(&mut self, events: &EventList, model: &Model) {
+        for e in events.get_events() {
+            match e {
+                Event::SelectedTournamentAndStageChanged { source, new_tournament_id, new_stage_id } if *source == self.linked_outline_id => {
+                    self.tournament_id = *new_tournament_id;
+                    self.stage_id = *new_stage_id;
+                    self.refresh(model);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    pub fn get_dom_table(&self) -> &HtmlTableElement {
+        &self.dom_table
+    }
+
+    pub fn new(id: UiElementId, model: &Model, linked_outline_id: UiElementId) -> SwissView {
+        let dom_table = create_element::<HtmlTableElement>("table");
+
+        let head: HtmlTableSectionElement = dom_table.create_t_head().dyn_into().expect("Cast failed");
+        let head_row: HtmlTableRowElement = head.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        head_row.set_inner_html(r#"<th colspan="4"><h3>Swiss rounds</h3></th>"#);
+
+        let body: HtmlTableSectionElement = dom_table.create_t_body().dyn_into().expect("Cast failed");
+
+        let foot: HtmlTableSectionElement = dom_table.create_t_foot().dyn_into().expect("Cast failed");
+        let foot_row: HtmlTableRowElement = foot.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        let cell = foot_row.insert_cell().expect("Failed to insert cell");
+        cell.set_attribute("colspan", "4").expect("Failed to set attribute");
+        let generate_round_button: HtmlButtonElement = create_element("button");
+        cell.append_child(&generate_round_button).expect("Failed to append button");
+
+        let mut result = SwissView { id, tournament_id: None, stage_id: None, linked_outline_id, dom_table, body, generate_round_button, closures: vec![] };
+
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::SwissView(this)) = ui.get_element(id) {
+                this.on_generate_round_button_click(model);
+            }
+        });
+        result.generate_round_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        result.refresh(model);
+
+        result
+    }
+
+    fn refresh(&mut self, model: &Model) {
+        let mut show = false;
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Some(s) = model.get_stage(tournament_id, stage_id) {
+                if let StageKind::Swiss { .. } = s.kind {
+                    show = true;
+                }
+            }
+        }
+        self.dom_table.style().set_property("display",
+            if show { "block" } else { "none" }).expect("Failed to set style");
+
+        while self.body.rows().length() > 0 {
+            self.body.delete_row(0).expect("Failed to delete row");
+            //TODO: remove closures for result buttons?
+        }
+
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Some(stage) = model.get_stage(tournament_id, stage_id) {
+                if let StageKind::Swiss { rounds, pairings, .. } = &stage.kind {
+                    for (round, round_pairings) in pairings.iter().enumerate() {
+                        let heading_row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+                        heading_row.set_inner_html(&format!(r#"<th colspan="4">Round {}</th>"#, round + 1));
+                        for (i, p) in round_pairings.iter().enumerate() {
+                            self.add_pairing_row(p, round, i, stage);
+                        }
+                    }
+
+                    for (status, label) in [(SwissStatus::Advanced, "Advanced"), (SwissStatus::Eliminated, "Eliminated")] {
+                        let names: Vec<&str> = stage.teams.values().filter(|t| stage.get_swiss_status(t.id) == status).map(|t| &t.name[..]).collect();
+                        if !names.is_empty() {
+                            let row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+                            let cell = row.insert_cell().expect("Failed to insert cell");
+                            cell.set_attribute("colspan", "4").expect("Failed to set attribute");
+                            cell.set_inner_text(&format!("{label}: {}", names.join(", ")));
+                        }
+                    }
+
+                    self.generate_round_button.set_inner_text(&format!("Generate round {} of {rounds}", pairings.len() + 1));
+                    self.generate_round_button.set_disabled(pairings.len() >= *rounds as usize);
+                }
+            }
+        }
+    }
+
+    fn add_pairing_row(&mut self, p: &SwissPairing, round: usize, pairing: usize, stage: &Stage) {
+        let new_row: HtmlTableRowElement = self.body.insert_row().expect("Failed to insert row").dyn_into().expect("Cast failed");
+        let m = p.match_id.and_then(|m| stage.matches.get(&m));
+
+        let team_a_cell = new_row.insert_cell().expect("Failed to insert cell");
+        team_a_cell.set_inner_text(&stage.teams.get(&p.team_a).map(|t| t.name.clone()).unwrap_or("???".to_string()));
+
+        // The match might have its teams the other way round to the pairing
+        let score_cell = new_row.insert_cell().expect("Failed to insert cell");
+        score_cell.set_inner_text(&match m {
+            Some(m) if m.team_a == p.team_a => format!("{} - {}", m.team_a_score, m.team_b_score),
+            Some(m) => format!("{} - {}", m.team_b_score, m.team_a_score),
+            None => "vs".to_string(),
+        });
+
+        let team_b_cell = new_row.insert_cell().expect("Failed to insert cell");
+        team_b_cell.set_inner_text(&stage.teams.get(&p.team_b).map(|t| t.name.clone()).unwrap_or("???".to_string()));
+
+        let winner = m.and_then(|m| m.get_winner());
+        if winner.is_some() {
+            team_a_cell.set_class_name(if winner == Some(p.team_a) { "swiss-winner" } else { "swiss-loser" });
+            team_b_cell.set_class_name(if winner == Some(p.team_b) { "swiss-winner" } else { "swiss-loser" });
+        }
+
+        let cell = new_row.insert_cell().expect("Failed to insert cell");
+        let result_button = create_html_element("button");
+        result_button.set_inner_text("Result");
+        cell.append_child(&result_button).expect("Failed to append button");
+        let id = self.id;
+        let click_closure = create_callback(move |model, ui| {
+            if let Some(UiElement::SwissView(this)) = ui.get_element(id) {
+                this.on_result_button_click(model, round, pairing);
+            }
+        });
+        result_button.set_onclick(Some(click_closure.as_ref().unchecked_ref()));
+        self.closures.push(click_closure); // Needs to be kept alive
+    }
+
+    fn on_generate_round_button_click(&self, model: &mut Model) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            if let Err(e) = model.generate_swiss_round(tournament_id, stage_id) {
+                window().unwrap().alert_with_message(&format!("Couldn't generate the next round. {e}")).expect("Failed to alert");
+            }
+        }
+    }
+
+    fn on_result_button_click(&self, model: &mut Model, round: usize, pairing: usize) {
+        if let (Some(tournament_id), Some(stage_id)) = (self.tournament_id, self.stage_id) {
+            let (message, current_score, series_format) = match model.get_stage(tournament_id, stage_id) {
+                Some(stage) => match &stage.kind {
+                    StageKind::Swiss { pairings, .. } => match pairings.get(round).and_then(|r| r.get(pairing)) {
+                        Some(p) => {
+                            let current_score = p.match_id.and_then(|m| stage.matches.get(&m))
+                                .map(|m| if m.team_a == p.team_a { (m.team_a_score, m.team_b_score) } else { (m.team_b_score, m.team_a_score) })
+                                .unwrap_or(stage.series_format.get_sweep_score());
+                            let name = |t| stage.teams.get(&t).map(|t| t.name.clone()).unwrap_or("???".to_string());
+                            (format!("Enter the score for {} vs {} ({}):", name(p.team_a), name(p.team_b), stage.series_format.get_name()),
+                                current_score, stage.series_format)
+                        }
+                        None => return,
+                    }
+                    _ => return,
+                }
+                None => return,
+            };
+            if let Some((team_a_score, team_b_score)) = prompt_for_score(&message, current_score) {
+                if let Err(_) = model.set_swiss_pairing_score(tournament_id, stage_id, round, pairing, team_a_score, team_b_score) {
+                    window().unwrap().alert_with_message(&format!("Couldn't record the result. The score needs to be valid for a {} match.",
+                        series_format.get_name())).expect("Failed to alert");
+                }
+            }
+        }
+    }
+}
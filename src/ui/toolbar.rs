@@ -0,0 +1,228 @@
+use log::error;
+use wasm_bindgen::{JsCast, prelude::Closure};
+use web_sys::{HtmlDivElement, HtmlButtonElement, HtmlInputElement, HtmlTextAreaElement, KeyboardEvent, window};
+
+use crate::{dom::create_element, model::Model, model::tournament::TournamentId, ui::{create_callback, create_view_callback, UiElementId, UiElement}, with_globals};
+
+/// The keyboard shortcuts handled by the toolbar.
+#[derive(Clone, Copy)]
+enum Shortcut {
+    Undo,
+    Redo,
+}
+
+/// Buttons for things which apply to everything, rather than to a particular tournament or stage.
+pub struct Toolbar {
+    id: UiElementId,
+
+    div: HtmlDivElement,
+    lock_button: HtmlButtonElement,
+    undo_button: HtmlButtonElement,
+    redo_button: HtmlButtonElement,
+    what_if_button: HtmlButtonElement,
+    commit_what_if_button: HtmlButtonElement,
+
+    #[allow(dyn_drop)]
+    closures: Vec<Box<dyn Drop>>,
+}
+
+impl Toolbar {
+    pub fn get_id(&self) -> UiElementId {
+        self.id
+    }
+
+    pub fn tournament_changed(&mut self, model: &Model, _tournament_id: TournamentId) {
+        self.refresh(model);
+    }
+
+    pub fn get_div(&self) -> &HtmlDivElement {
+        &self.div
+    }
+
+    pub fn new(id: UiElementId, model: &Model) -> Toolbar {
+        let div = create_element::<HtmlDivElement>("div");
+        div.set_class_name("toolbar");
+
+        let lock_button: HtmlButtonElement = create_element("button");
+        lock_button.set_title("Stop any changes being made, e.g. when just looking at the results, so that nothing is changed by an accidental click");
+        div.append_child(&lock_button).expect("Failed to append child");
+
+        let undo_button: HtmlButtonElement = create_element("button");
+        undo_button.set_inner_text("Undo");
+        undo_button.set_title("Undo the last change (Ctrl+Z)");
+        div.append_child(&undo_button).expect("Failed to append child");
+
+        let redo_button: HtmlButtonElement = create_element("button");
+        redo_button.set_inner_text("Redo");
+        redo_button.set_title("Redo the last undone change (Ctrl+Y)");
+        div.append_child(&redo_button).expect("Failed to append child");
+
+        let what_if_button: HtmlButtonElement = create_element("button");
+        what_if_button.set_title("Enter results to see what would happen, without changing the real results");
+        div.append_child(&what_if_button).expect("Failed to append child");
+
+        let commit_what_if_button: HtmlButtonElement = create_element("button");
+        commit_what_if_button.set_inner_text("Keep what-if results");
+        commit_what_if_button.set_title("Make all the results entered in what-if mode real");
+        div.append_child(&commit_what_if_button).expect("Failed to append child");
+
+        let mut result = Toolbar { id, div, lock_button, undo_button, redo_button, what_if_button, commit_what_if_button, closures: vec![] };
+
+        // This needs to work while locked, otherwise there would be no way to unlock!
+        let click_closure = Box::new(create_view_callback(move |model, ui| {
+            if let Some(UiElement::Toolbar(this)) = ui.get_element_mut(id) {
+                this.on_lock_button_click(model);
+            }
+        }));
+        result.lock_button.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = Box::new(create_callback(move |model, ui| {
+            if let Some(UiElement::Toolbar(this)) = ui.get_element_mut(id) {
+                this.on_undo_button_click(model);
+            }
+        }));
+        result.undo_button.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = Box::new(create_callback(move |model, ui| {
+            if let Some(UiElement::Toolbar(this)) = ui.get_element_mut(id) {
+                this.on_redo_button_click(model);
+            }
+        }));
+        result.redo_button.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        // The keyboard shortcuts apply to the whole page, not just when the toolbar has focus.
+        // This is called for every key that's pressed, so rather than using create_callback_with_arg (which would update everything
+        // each time), the globals are only touched for the shortcuts themselves.
+        let keydown_closure = Box::new(Closure::<dyn FnMut(KeyboardEvent)>::new(move |e: KeyboardEvent| {
+            if let Some(shortcut) = Toolbar::get_shortcut(&e) {
+                e.prevent_default();
+                with_globals(|model, ui| {
+                    if model.is_locked() {
+                        return;
+                    }
+                    if let Some(UiElement::Toolbar(this)) = ui.get_element_mut(id) {
+                        this.on_shortcut(model, shortcut);
+                    }
+                    model.process_updates(ui);
+                    ui.process_events(model);
+                });
+            }
+        }));
+        window().unwrap().document().unwrap().add_event_listener_with_callback("keydown", keydown_closure.as_ref().as_ref().unchecked_ref())
+            .expect("Failed to add event listener");
+        result.closures.push(keydown_closure); // Needs to be kept alive
+
+        let click_closure = Box::new(create_callback(move |model, ui| {
+            if let Some(UiElement::Toolbar(this)) = ui.get_element_mut(id) {
+                this.on_what_if_button_click(model);
+            }
+        }));
+        result.what_if_button.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        let click_closure = Box::new(create_callback(move |model, ui| {
+            if let Some(UiElement::Toolbar(this)) = ui.get_element_mut(id) {
+                this.on_commit_what_if_button_click(model);
+            }
+        }));
+        result.commit_what_if_button.set_onclick(Some(click_closure.as_ref().as_ref().unchecked_ref()));
+        result.closures.push(click_closure); // Needs to be kept alive
+
+        result.refresh(model);
+
+        result
+    }
+
+    fn refresh(&mut self, model: &Model) {
+        let locked = model.is_locked();
+        self.lock_button.set_inner_text(if locked { "Unlock" } else { "Lock" });
+        self.undo_button.set_disabled(locked || !model.can_undo());
+        self.redo_button.set_disabled(locked || !model.can_redo());
+        self.what_if_button.set_disabled(locked);
+        self.commit_what_if_button.set_disabled(locked);
+
+        let what_if = model.is_what_if();
+        self.what_if_button.set_inner_text(if what_if { "Leave what-if mode" } else { "What-if mode" });
+        self.commit_what_if_button.style().set_property("display", if what_if { "inline" } else { "none" }).expect("Failed to set style");
+
+        // Make it obvious that the results being shown aren't real
+        let body = window().unwrap().document().unwrap().body().unwrap();
+        body.class_list().toggle_with_force("what-if", what_if).expect("Failed to toggle class");
+        body.class_list().toggle_with_force("locked", locked).expect("Failed to toggle class");
+    }
+
+    fn on_lock_button_click(&mut self, model: &mut Model) {
+        model.set_locked(!model.is_locked());
+        // Nothing in the data has changed, so nobody else will be told about this
+        self.refresh(model);
+    }
+
+    fn on_undo_button_click(&mut self, model: &mut Model) {
+        if let Err(_) = model.undo() {
+            error!("Nothing to undo");
+        }
+        self.refresh(model);
+    }
+
+    fn on_redo_button_click(&mut self, model: &mut Model) {
+        if let Err(_) = model.redo() {
+            error!("Nothing to redo");
+        }
+        self.refresh(model);
+    }
+
+    /// Works out which shortcut (if any) a key press is for. Text fields keep their own undo and redo, so nothing is a shortcut in them.
+    fn get_shortcut(e: &KeyboardEvent) -> Option<Shortcut> {
+        if !e.ctrl_key() && !e.meta_key() {
+            return None;
+        }
+        if let Some(target) = e.target() {
+            if target.is_instance_of::<HtmlInputElement>() || target.is_instance_of::<HtmlTextAreaElement>() {
+                return None;
+            }
+        }
+        // Ctrl+Shift+Z is also a common shortcut for redo
+        match e.key().to_lowercase().as_str() {
+            "z" if !e.shift_key() => Some(Shortcut::Undo),
+            "z" | "y" => Some(Shortcut::Redo),
+            _ => None,
+        }
+    }
+
+    fn on_shortcut(&mut self, model: &mut Model, shortcut: Shortcut) {
+        let result = match shortcut {
+            Shortcut::Undo => model.undo(),
+            Shortcut::Redo => model.redo(),
+        };
+        // Unlike the buttons, the shortcuts can be pressed when there's nothing to undo or redo, so that's not an error
+        if result.is_ok() {
+            self.refresh(model);
+        }
+    }
+
+    fn on_what_if_button_click(&mut self, model: &mut Model) {
+        if model.is_what_if() {
+            if window().unwrap().confirm_with_message("Are you sure you want to leave what-if mode? All the results entered in what-if mode will be lost.") == Ok(true) {
+                if let Err(_) = model.leave_what_if() {
+                    error!("Failed to leave what-if mode");
+                }
+            }
+        } else if let Err(_) = model.enter_what_if() {
+            error!("Failed to enter what-if mode");
+        }
+        // There might not be any tournaments to be told about changing, so make sure this is up to date
+        self.refresh(model);
+    }
+
+    fn on_commit_what_if_button_click(&mut self, model: &mut Model) {
+        if window().unwrap().confirm_with_message("Are you sure you want to keep all the results entered in what-if mode? They will replace the real results.") == Ok(true) {
+            if let Err(_) = model.commit_what_if() {
+                error!("Failed to commit what-if results");
+            }
+        }
+        self.refresh(model);
+    }
+}
@@ -0,0 +1,621 @@
+// A command-line tool to view and edit a serialized Model, without needing a browser.
+// This is useful for scripting result entry, e.g. during a broadcast.
+//
+// The model is either a file exported from the web app (with the Export button), or a directory containing a file for each
+// of the values that the web app saves in localStorage (e.g. "tournament-tracker-model"). Either way, data from older versions
+// is upgraded when it's loaded.
+// If the file doesn't exist then a new one is created.
+// Each invocation loads the model, runs a single command and (if the command changed anything) saves it back in the same form.
+//
+// Usage: tournament-tracker-cli <model-file-or-directory> <command> [args...]
+// Run with no arguments for a list of commands.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use tournament_tracker::model::Model;
+use tournament_tracker::model::export::ImportMode;
+use tournament_tracker::model::storage::FileStorageBackend;
+use tournament_tracker::model::form::{get_current_streak, get_longest_streak, MatchResult};
+use tournament_tracker::model::simulation::format_chance;
+use tournament_tracker::model::standings::to_ordinal;
+use tournament_tracker::model::tournament::{FixtureTeam, Outcome, ScoringSystem, SeriesFormat, Stage, StageId, StageKind, TeamId, Tiebreaker, TournamentId};
+
+const USAGE: &str = "Usage: tournament-tracker-cli <model-file-or-directory> <command> [args...]
+
+The model is either a file exported from the web app (created if it doesn't exist),
+or a directory with a file for each value that the web app saves in localStorage.
+
+Commands:
+    list
+    add-tournament <name>
+    add-stage-round-robin <tournament-id> <name> [legs]
+    add-stage-bracket <tournament-id> <name>
+    add-stage-swiss <tournament-id> <name> <rounds> <wins-to-advance> <losses-to-be-eliminated>
+    add-team <tournament-id> <stage-id> <name>
+    add-match <tournament-id> <stage-id> <team-a> <team-b> <team-a-score> <team-b-score>
+    add-tiebreaker-match <tournament-id> <stage-id> <team-a> <team-b> <team-a-score> <team-b-score>
+    add-fixture <tournament-id> <stage-id> <x> <y> <fixture-team-a> <fixture-team-b>
+    set-match-score <tournament-id> <stage-id> <match-id> <team-a-score> <team-b-score>
+    set-match-tiebreaker <tournament-id> <stage-id> <match-id> <true|false>
+    set-fixture-score <tournament-id> <stage-id> <fixture-id> <team-a-score> <team-b-score>
+    schedule-match <tournament-id> <stage-id> <team-a> <team-b> <round>
+    set-scheduled-time <tournament-id> <stage-id> <scheduled-match> <time>
+    set-round-label <tournament-id> <stage-id> <round> <label>
+    delete-scheduled-match <tournament-id> <stage-id> <scheduled-match>
+    set-scheduled-score <tournament-id> <stage-id> <scheduled-match> <team-a-score> <team-b-score>
+    set-swiss-score <tournament-id> <stage-id> <round> <pairing> <team-a-score> <team-b-score>
+    set-series-format <tournament-id> <stage-id> <series-format>
+    set-scoring-system <tournament-id> <stage-id> <scoring-system>
+    set-advancing-teams <tournament-id> <stage-id> <number>
+    delete-tournament <tournament-id>
+    delete-stage <tournament-id> <stage-id>
+    delete-team <tournament-id> <stage-id> <team>
+    delete-match <tournament-id> <stage-id> <match-id>
+    delete-fixture <tournament-id> <stage-id> <fixture-id>
+    rename-tournament <tournament-id> <new-name>
+    rename-stage <tournament-id> <stage-id> <new-name>
+    set-tiebreakers <tournament-id> <stage-id> <comma-separated-tiebreakers>
+    standings <tournament-id> <stage-id>
+    round-robin-table <tournament-id> <stage-id>
+    fixtures <tournament-id> <stage-id>
+    schedule <tournament-id> <stage-id>
+    simulate <tournament-id> <stage-id>
+    generate-schedule <tournament-id> <stage-id>
+    swiss <tournament-id> <stage-id>
+    generate-swiss-round <tournament-id> <stage-id>
+    generate-single-elimination <tournament-id> <stage-id> <comma-separated-teams> [third-place]
+    generate-double-elimination <tournament-id> <stage-id> <comma-separated-teams> [bracket-reset]
+    generate-gsl-group <tournament-id> <stage-id> <comma-separated-teams>
+
+Teams can be given either by ID or by name. Teams for generated brackets are given in seed order (best first).
+Fixture teams are either a team (as above), 'winner:<fixture-id>' or 'loser:<fixture-id>'.
+Scheduled matches and rounds are numbered from 1, as shown by the 'schedule' command. An empty time or label clears it.
+Swiss rounds and pairings are numbered from 1, as shown by the 'swiss' command.
+Tiebreakers are any of: head-to-head, mini-table, score-differential, strength-of-victory, buchholz.
+Series formats are either 'bo<N>' for best-of-N games (N must be odd) or 'fixed-<N>' for exactly N games, allowing draws.
+Scoring systems are 'win-loss', 'points:<win>/<draw>/<loss>' for points per match result (e.g. 'points:3/1/0')
+or 'games:<points>' for points per game won. Add '+<points>' to give bonus points for a sweep, e.g. 'games:1+1'.";
+
+/// How many of the most recent results to show in the standings' form column.
+const FORM_LENGTH: usize = 5;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 2 {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    }
+
+    let model_path = PathBuf::from(&args[0]);
+    let is_dir = model_path.is_dir();
+    let loaded = if is_dir {
+        // The model is linked to the storage, so will save itself back there
+        Model::load(Box::new(FileStorageBackend::new(model_path.clone())))
+    } else {
+        load_export_file(&model_path)
+    };
+    let mut model = match loaded {
+        Ok(m) => m,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run_command(&mut model, &args[1], &args[2..]) {
+        Ok(Changed::No) => ExitCode::SUCCESS,
+        Ok(Changed::Yes) => {
+            let saved = if is_dir {
+                model.save();
+                Ok(())
+            } else {
+                save_export_file(&model, &model_path)
+            };
+            if let Err(e) = saved {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Loads a file exported from the web app, going through the same upgrade path as importing it there.
+/// If the file doesn't exist yet then this starts from an empty model, which will be saved as a new file.
+fn load_export_file(path: &Path) -> Result<Model, String> {
+    if !path.exists() {
+        return Ok(Model::new());
+    }
+    let s = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let mut model = Model::new();
+    model.import_json(&s, ImportMode::Replace)?;
+    Ok(model)
+}
+
+/// Saves the model in the same format as the web app exports it, so that it can be imported there again.
+fn save_export_file(model: &Model, path: &Path) -> Result<(), String> {
+    let s = model.export_json()?;
+    std::fs::write(path, s).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Whether a command modified the model, and so it needs saving.
+enum Changed {
+    Yes, No,
+}
+
+fn run_command(model: &mut Model, command: &str, args: &[String]) -> Result<Changed, String> {
+    match (command, args) {
+        ("list", []) => {
+            print_list(model);
+            Ok(Changed::No)
+        }
+        ("add-tournament", [name]) => {
+            let id = model.add_tournament(name.clone());
+            println!("{id}");
+            Ok(Changed::Yes)
+        }
+        ("add-stage-round-robin", [t, name]) => {
+            let id = model.add_stage_round_robin(parse_id(t)?, name.clone(), 1).ok_or("Failed to add stage")?;
+            println!("{id}");
+            Ok(Changed::Yes)
+        }
+        ("add-stage-round-robin", [t, name, legs]) => {
+            let legs = legs.parse().map_err(|_| format!("Invalid number of legs '{legs}'"))?;
+            let id = model.add_stage_round_robin(parse_id(t)?, name.clone(), legs).ok_or("Failed to add stage")?;
+            println!("{id}");
+            Ok(Changed::Yes)
+        }
+        ("set-match-score", [t, s, m, team_a_score, team_b_score]) => {
+            model.set_match_score(parse_id(t)?, parse_id(s)?, parse_id(m)?, parse_score(team_a_score)?, parse_score(team_b_score)?)
+                .map_err(|_| "Failed to set match score")?;
+            Ok(Changed::Yes)
+        }
+        ("add-stage-bracket", [t, name]) => {
+            let id = model.add_stage_bracket(parse_id(t)?, name.clone()).ok_or("Failed to add stage")?;
+            println!("{id}");
+            Ok(Changed::Yes)
+        }
+        ("add-stage-swiss", [t, name, rounds, advance_wins, eliminate_losses]) => {
+            let parse = |n: &str| n.parse().map_err(|_| format!("Invalid number '{n}'"));
+            let id = model.add_stage_swiss(parse_id(t)?, name.clone(), parse(rounds)?, parse(advance_wins)?, parse(eliminate_losses)?)
+                .ok_or("Failed to add stage")?;
+            println!("{id}");
+            Ok(Changed::Yes)
+        }
+        ("add-team", [t, s, name]) => {
+            let id = model.add_team(parse_id(t)?, parse_id(s)?, name.clone()).ok_or("Failed to add team")?;
+            println!("{id}");
+            Ok(Changed::Yes)
+        }
+        ("add-match", [t, s, team_a, team_b, team_a_score, team_b_score]) => {
+            let (t, s) = (parse_id(t)?, parse_id(s)?);
+            let stage = get_stage(model, t, s)?;
+            let team_a = parse_team(stage, team_a)?;
+            let team_b = parse_team(stage, team_b)?;
+            let id = model.add_match(t, s, team_a, team_b, parse_score(team_a_score)?, parse_score(team_b_score)?).ok_or("Failed to add match")?;
+            println!("{id}");
+            Ok(Changed::Yes)
+        }
+        ("add-tiebreaker-match", [t, s, team_a, team_b, team_a_score, team_b_score]) => {
+            let (t, s) = (parse_id(t)?, parse_id(s)?);
+            let stage = get_stage(model, t, s)?;
+            let team_a = parse_team(stage, team_a)?;
+            let team_b = parse_team(stage, team_b)?;
+            let id = model.add_tiebreaker_match(t, s, team_a, team_b, parse_score(team_a_score)?, parse_score(team_b_score)?).ok_or("Failed to add match")?;
+            println!("{id}");
+            Ok(Changed::Yes)
+        }
+        ("set-fixture-score", [t, s, f, team_a_score, team_b_score]) => {
+            model.set_fixture_score(parse_id(t)?, parse_id(s)?, parse_id(f)?, parse_score(team_a_score)?, parse_score(team_b_score)?)
+                .map_err(|_| "Failed to set fixture score")?;
+            Ok(Changed::Yes)
+        }
+        ("schedule-match", [t, s, team_a, team_b, round]) => {
+            let (t, s) = (parse_id(t)?, parse_id(s)?);
+            let stage = get_stage(model, t, s)?;
+            let team_a = parse_team(stage, team_a)?;
+            let team_b = parse_team(stage, team_b)?;
+            let index = model.add_scheduled_match(t, s, team_a, team_b, parse_number(round)? as u32).ok_or("Failed to schedule match")?;
+            println!("{}", index + 1);
+            Ok(Changed::Yes)
+        }
+        ("set-scheduled-time", [t, s, index, time]) => {
+            let time = if time.is_empty() { None } else { Some(time.clone()) };
+            model.set_scheduled_match_time(parse_id(t)?, parse_id(s)?, parse_number(index)?, time).map_err(|_| "Failed to set scheduled match time")?;
+            Ok(Changed::Yes)
+        }
+        ("set-round-label", [t, s, round, label]) => {
+            let label = if label.is_empty() { None } else { Some(label.clone()) };
+            model.set_schedule_round_label(parse_id(t)?, parse_id(s)?, parse_number(round)? as u32, label).map_err(|_| "Failed to set round label")?;
+            Ok(Changed::Yes)
+        }
+        ("delete-scheduled-match", [t, s, index]) => {
+            model.delete_scheduled_match(parse_id(t)?, parse_id(s)?, parse_number(index)?).map_err(|_| "Failed to delete scheduled match")?;
+            Ok(Changed::Yes)
+        }
+        ("set-scheduled-score", [t, s, index, team_a_score, team_b_score]) => {
+            model.set_scheduled_match_score(parse_id(t)?, parse_id(s)?, parse_number(index)?, parse_score(team_a_score)?, parse_score(team_b_score)?)
+                .map_err(|_| "Failed to set scheduled match score")?;
+            Ok(Changed::Yes)
+        }
+        ("set-swiss-score", [t, s, round, pairing, team_a_score, team_b_score]) => {
+            model.set_swiss_pairing_score(parse_id(t)?, parse_id(s)?, parse_number(round)?, parse_number(pairing)?, parse_score(team_a_score)?, parse_score(team_b_score)?)
+                .map_err(|_| "Failed to set Swiss score")?;
+            Ok(Changed::Yes)
+        }
+        ("set-series-format", [t, s, series_format]) => {
+            let series_format = SeriesFormat::from_name(series_format).ok_or(format!("Unknown series format '{series_format}'"))?;
+            model.set_stage_series_format(parse_id(t)?, parse_id(s)?, series_format)
+                .map_err(|_| "Failed to set series format - do any existing matches have scores which aren't valid in the new format?")?;
+            Ok(Changed::Yes)
+        }
+        ("set-scoring-system", [t, s, scoring_system]) => {
+            let scoring_system = ScoringSystem::from_name(scoring_system).ok_or(format!("Unknown scoring system '{scoring_system}'"))?;
+            model.set_stage_scoring_system(parse_id(t)?, parse_id(s)?, scoring_system).map_err(|_| "Failed to set scoring system")?;
+            Ok(Changed::Yes)
+        }
+        ("set-advancing-teams", [t, s, advancing_teams]) => {
+            let advancing_teams = advancing_teams.parse().map_err(|_| format!("Invalid number '{advancing_teams}'"))?;
+            model.set_stage_advancing_teams(parse_id(t)?, parse_id(s)?, advancing_teams).map_err(|_| "Failed to set advancing teams - it can't be more than the number of teams")?;
+            Ok(Changed::Yes)
+        }
+        ("set-match-tiebreaker", [t, s, m, is_tiebreaker]) => {
+            let is_tiebreaker = is_tiebreaker.parse().map_err(|_| format!("Expected true or false, got '{is_tiebreaker}'"))?;
+            model.set_match_tiebreaker(parse_id(t)?, parse_id(s)?, parse_id(m)?, is_tiebreaker).map_err(|_| "Failed to set match tiebreaker")?;
+            Ok(Changed::Yes)
+        }
+        ("add-fixture", [t, s, x, y, team_a, team_b]) => {
+            let (t, s) = (parse_id(t)?, parse_id(s)?);
+            let stage = get_stage(model, t, s)?;
+            let team_a = parse_fixture_team(stage, team_a)?;
+            let team_b = parse_fixture_team(stage, team_b)?;
+            let layout = (x.parse().map_err(|_| format!("Invalid x '{x}'"))?, y.parse().map_err(|_| format!("Invalid y '{y}'"))?);
+            let id = model.add_fixture(t, s, layout, team_a, team_b).ok_or("Failed to add fixture")?;
+            println!("{id}");
+            Ok(Changed::Yes)
+        }
+        ("delete-tournament", [t]) => {
+            model.delete_tournament(parse_id(t)?).map_err(|_| "Failed to delete tournament")?;
+            Ok(Changed::Yes)
+        }
+        ("delete-stage", [t, s]) => {
+            model.delete_stage(parse_id(t)?, parse_id(s)?).map_err(|_| "Failed to delete stage")?;
+            Ok(Changed::Yes)
+        }
+        ("delete-team", [t, s, team]) => {
+            let (t, s) = (parse_id(t)?, parse_id(s)?);
+            let team = parse_team(get_stage(model, t, s)?, team)?;
+            model.delete_team(t, s, team).map_err(|_| "Failed to delete team")?;
+            Ok(Changed::Yes)
+        }
+        ("delete-match", [t, s, m]) => {
+            model.delete_match(parse_id(t)?, parse_id(s)?, parse_id(m)?).map_err(|_| "Failed to delete match")?;
+            Ok(Changed::Yes)
+        }
+        ("delete-fixture", [t, s, f]) => {
+            model.delete_fixture(parse_id(t)?, parse_id(s)?, parse_id(f)?).map_err(|_| "Failed to delete fixture (other fixtures might be linked to it)")?;
+            Ok(Changed::Yes)
+        }
+        ("rename-tournament", [t, new_name]) => {
+            model.rename_tournament(parse_id(t)?, new_name).map_err(|_| "Failed to rename tournament")?;
+            Ok(Changed::Yes)
+        }
+        ("rename-stage", [t, s, new_name]) => {
+            model.rename_stage(parse_id(t)?, parse_id(s)?, new_name).map_err(|_| "Failed to rename stage")?;
+            Ok(Changed::Yes)
+        }
+        ("set-tiebreakers", [t, s, tiebreakers]) => {
+            let tiebreakers = tiebreakers.split(',').map(|n| n.trim()).filter(|n| !n.is_empty())
+                .map(|n| Tiebreaker::from_name(n).ok_or(format!("Unknown tiebreaker '{n}'")))
+                .collect::<Result<Vec<Tiebreaker>, String>>()?;
+            model.set_stage_tiebreakers(parse_id(t)?, parse_id(s)?, tiebreakers).map_err(|_| "Failed to set tiebreakers")?;
+            Ok(Changed::Yes)
+        }
+        ("standings", [t, s]) => {
+            print_standings(get_stage(model, parse_id(t)?, parse_id(s)?)?);
+            Ok(Changed::No)
+        }
+        ("round-robin-table", [t, s]) => {
+            print_round_robin_table(get_stage(model, parse_id(t)?, parse_id(s)?)?);
+            Ok(Changed::No)
+        }
+        ("generate-single-elimination", [t, s, teams]) => generate_single_elimination(model, t, s, teams, false),
+        ("generate-single-elimination", [t, s, teams, third_place]) if third_place == "third-place" => generate_single_elimination(model, t, s, teams, true),
+        ("generate-double-elimination", [t, s, teams]) => generate_double_elimination(model, t, s, teams, false),
+        ("generate-double-elimination", [t, s, teams, reset]) if reset == "bracket-reset" => generate_double_elimination(model, t, s, teams, true),
+        ("generate-gsl-group", [t, s, teams]) => {
+            let (t, s) = (parse_id(t)?, parse_id(s)?);
+            let seeded_teams = parse_teams(get_stage(model, t, s)?, teams)?;
+            model.generate_gsl_group(t, s, &seeded_teams).map_err(|_| "Failed to generate group - it must be an empty bracket stage, with exactly four different teams")?;
+            Ok(Changed::Yes)
+        }
+        ("fixtures", [t, s]) => {
+            print_fixtures(get_stage(model, parse_id(t)?, parse_id(s)?)?);
+            Ok(Changed::No)
+        }
+        ("schedule", [t, s]) => {
+            print_schedule(get_stage(model, parse_id(t)?, parse_id(s)?)?);
+            Ok(Changed::No)
+        }
+        ("simulate", [t, s]) => {
+            print_simulation(get_stage(model, parse_id(t)?, parse_id(s)?)?)?;
+            Ok(Changed::No)
+        }
+        ("generate-schedule", [t, s]) => {
+            model.generate_round_robin_schedule(parse_id(t)?, parse_id(s)?)
+                .map_err(|_| "Failed to generate schedule - it must be a round robin stage with at least two teams, and no schedule yet")?;
+            Ok(Changed::Yes)
+        }
+        ("swiss", [t, s]) => {
+            print_swiss(get_stage(model, parse_id(t)?, parse_id(s)?)?);
+            Ok(Changed::No)
+        }
+        ("generate-swiss-round", [t, s]) => {
+            model.generate_swiss_round(parse_id(t)?, parse_id(s)?)
+                .map_err(|e| format!("Failed to generate round: {e}"))?;
+            Ok(Changed::Yes)
+        }
+        _ => Err(format!("Unknown command or wrong number of arguments: {command} {}\n\n{USAGE}", args.join(" "))),
+    }
+}
+
+fn parse_id(s: &str) -> Result<usize, String> {
+    s.parse().map_err(|_| format!("Invalid ID '{s}'"))
+}
+
+/// Parses a 1-based number (e.g. a Swiss round) into a 0-based index.
+fn parse_number(s: &str) -> Result<usize, String> {
+    s.parse::<usize>().ok().and_then(|n| n.checked_sub(1)).ok_or(format!("Invalid number '{s}'"))
+}
+
+fn parse_score(s: &str) -> Result<u32, String> {
+    s.parse().map_err(|_| format!("Invalid score '{s}'"))
+}
+
+/// Looks up a team by ID, falling back to looking up by name.
+fn parse_team(stage: &Stage, s: &str) -> Result<TeamId, String> {
+    if let Ok(id) = s.parse::<TeamId>() {
+        if stage.teams.contains_key(&id) {
+            return Ok(id);
+        }
+    }
+    stage.teams.values().find(|t| t.name == s).map(|t| t.id).ok_or(format!("No team '{s}' in stage '{}'", stage.name))
+}
+
+fn generate_single_elimination(model: &mut Model, t: &str, s: &str, teams: &str, third_place_match: bool) -> Result<Changed, String> {
+    let (t, s) = (parse_id(t)?, parse_id(s)?);
+    let seeded_teams = parse_teams(get_stage(model, t, s)?, teams)?;
+    model.generate_single_elimination(t, s, &seeded_teams, third_place_match)
+        .map_err(|_| "Failed to generate bracket - it must be an empty bracket stage, with at least two different teams (four for a third place match)")?;
+    Ok(Changed::Yes)
+}
+
+fn generate_double_elimination(model: &mut Model, t: &str, s: &str, teams: &str, bracket_reset: bool) -> Result<Changed, String> {
+    let (t, s) = (parse_id(t)?, parse_id(s)?);
+    let seeded_teams = parse_teams(get_stage(model, t, s)?, teams)?;
+    model.generate_double_elimination(t, s, &seeded_teams, bracket_reset)
+        .map_err(|_| "Failed to generate bracket - it must be an empty bracket stage, with at least three different teams")?;
+    Ok(Changed::Yes)
+}
+
+fn parse_teams(stage: &Stage, s: &str) -> Result<Vec<TeamId>, String> {
+    s.split(',').map(|t| t.trim()).filter(|t| !t.is_empty()).map(|t| parse_team(stage, t)).collect()
+}
+
+fn parse_fixture_team(stage: &Stage, s: &str) -> Result<FixtureTeam, String> {
+    let linked = |outcome: Outcome, f: &str| parse_id(f).map(|fixture_id| FixtureTeam::Linked { fixture_id, outcome });
+    match s.split_once(':') {
+        Some(("winner", f)) => linked(Outcome::Winner, f),
+        Some(("loser", f)) => linked(Outcome::Loser, f),
+        _ => parse_team(stage, s).map(FixtureTeam::Fixed),
+    }
+}
+
+fn get_stage(model: &Model, tournament_id: TournamentId, stage_id: StageId) -> Result<&Stage, String> {
+    model.get_stage(tournament_id, stage_id).ok_or(format!("No stage {stage_id} in tournament {tournament_id}"))
+}
+
+fn print_list(model: &Model) {
+    for (tournament_id, tournament) in model.get_tournaments() {
+        println!("{tournament_id}: {}", tournament.name);
+        for (stage_id, stage) in &tournament.stages {
+            let kind = match stage.kind {
+                StageKind::RoundRobin { legs: 1, .. } => "round robin".to_string(),
+                StageKind::RoundRobin { legs, .. } => format!("{legs}-leg round robin"),
+                StageKind::Bracket { .. } => "bracket".to_string(),
+                StageKind::Swiss { rounds, advance_wins, eliminate_losses, .. } => format!("{rounds}-round swiss, {advance_wins} wins to advance, {eliminate_losses} losses to be eliminated"),
+            };
+            println!("    {stage_id}: {} ({kind}, {}, {})", stage.name, stage.series_format.get_name(), stage.scoring_system.get_name());
+            for (team_id, team) in &stage.teams {
+                println!("        {team_id}: {}", team.name);
+            }
+        }
+    }
+}
+
+fn print_standings(stage: &Stage) {
+    let show_points = stage.scoring_system.uses_points();
+    let clinch_statuses = stage.get_clinch_statuses();
+    let show_status = !clinch_statuses.is_empty();
+    let rows: Vec<Vec<String>> = stage.get_standings().iter().map(|e| {
+        let team_name = stage.teams.get(&e.team_id).map(|t| t.name.clone()).unwrap_or("?".to_string());
+        let tiebreak = e.separation.and_then(|s| s.get_tiebreak_desc()).unwrap_or_default();
+        let win_rate = e.get_win_rate().map(|r| format!("{:.0}%", r * 100.0)).unwrap_or("-".to_string());
+        let form = stage.get_form(e.team_id);
+        let recent_form: String = form.iter().skip(form.len().saturating_sub(FORM_LENGTH)).map(|f| f.result.to_char()).collect();
+        let streak = get_current_streak(&form).map(|s| s.get_desc()).unwrap_or_default();
+        let best = get_longest_streak(&form, MatchResult::Win).map(|s| s.get_desc()).unwrap_or_default();
+        let record = if stage.has_draws() { format!("{} - {} - {}", e.wins, e.draws, e.losses) } else { format!("{} - {}", e.wins, e.losses) };
+        let mut row = vec![e.get_position_desc(), team_name, record];
+        if show_points {
+            row.push(e.points.to_string());
+        }
+        row.extend([e.games_played.to_string(), win_rate, format!("{:+}", e.score_differential), recent_form, streak, best, tiebreak]);
+        if show_status {
+            let status = clinch_statuses.iter().find(|c| c.team_id == e.team_id);
+            row.push(status.and_then(|c| c.get_badge().or(c.get_explanation(&row[1]))).unwrap_or_default());
+        }
+        row
+    }).collect();
+
+    let mut headings = vec!["Pos", "Team", if stage.has_draws() { "W - D - L" } else { "W - L" }];
+    if show_points {
+        headings.push("Pts");
+    }
+    headings.extend(["GP", "Win %", "Diff", "Form", "Streak", "Best", "Tiebreak"]);
+    if show_status {
+        headings.push("Status");
+    }
+    print_text_table(&headings, &rows);
+}
+
+fn print_simulation(stage: &Stage) -> Result<(), String> {
+    let simulation = stage.simulate_remaining_matches().ok_or("Chances can only be worked out for round robin stages")?;
+    println!("{} remaining matches, {} {}", simulation.remaining_matches.len(), simulation.num_outcomes,
+        if simulation.exhaustive { "possible outcomes" } else { "random outcomes" });
+
+    let advancing_teams = std::cmp::min(stage.advancing_teams as usize, stage.teams.len());
+    let rows: Vec<Vec<String>> = simulation.teams.iter().map(|t| {
+        let mut row = vec![stage.teams.get(&t.team_id).map(|t| t.name.clone()).unwrap_or("?".to_string())];
+        if advancing_teams > 0 {
+            row.push(format_chance(t.get_top_chance(advancing_teams)));
+        }
+        row.extend(t.position_chances.iter().map(|c| format_chance(*c)));
+        row
+    }).collect();
+
+    let top_heading = format!("Top {advancing_teams}");
+    let position_headings: Vec<String> = (1..=stage.teams.len()).map(to_ordinal).collect();
+    let mut headings = vec!["Team"];
+    if advancing_teams > 0 {
+        headings.push(&top_heading);
+    }
+    headings.extend(position_headings.iter().map(|h| &h[..]));
+    print_text_table(&headings, &rows);
+    Ok(())
+}
+
+fn print_round_robin_table(stage: &Stage) {
+    let legs = match stage.kind {
+        StageKind::RoundRobin { legs, .. } => legs as usize,
+        _ => 1,
+    };
+
+    let mut headings = vec![""];
+    headings.extend(stage.teams.values().map(|t| &t.name[..]));
+
+    let rows: Vec<Vec<String>> = stage.teams.values().map(|team| {
+        let mut row = vec![team.name.clone()];
+        for &other_team_id in stage.teams.keys() {
+            // Same symbols as the RoundRobinTable in the web app
+            if team.id == other_team_id {
+                row.push("+".to_string());
+                continue;
+            }
+            let matches = stage.get_matches_between(team.id, other_team_id);
+            let num_scheduled = stage.get_unplayed_scheduled_matches_between(team.id, other_team_id).len();
+            let results: Vec<&str> = (0..std::cmp::max(legs, matches.len() + num_scheduled)).map(|leg| match matches.get(leg) {
+                Some(m) if m.get_winner() == Some(team.id) => "W",
+                Some(m) if m.is_draw() => "D",
+                Some(_) => "L",
+                None if leg < matches.len() + num_scheduled => "*",
+                None => "-",
+            }).collect();
+            row.push(results.join("/"));
+        }
+        row
+    }).collect();
+    print_text_table(&headings, &rows);
+}
+
+fn print_fixtures(stage: &Stage) {
+    let fixtures = match &stage.kind {
+        StageKind::Bracket { fixtures } => fixtures,
+        _ => return,
+    };
+    let rows: Vec<Vec<String>> = fixtures.values().map(|f| {
+        // The match might have its teams the other way round to the fixture
+        let team_a = stage.get_fixture_team(&f.team_a);
+        let score = f.match_id.and_then(|m| stage.matches.get(&m)).map(|m| {
+            if Some(m.team_a) == team_a { format!("{} - {}", m.team_a_score, m.team_b_score) } else { format!("{} - {}", m.team_b_score, m.team_a_score) }
+        }).unwrap_or_default();
+        vec![f.id.to_string(), f.team_a.to_pretty_desc(stage), score, f.team_b.to_pretty_desc(stage)]
+    }).collect();
+    print_text_table(&["Fixture", "Team A", "Score", "Team B"], &rows);
+
+    let team_names = |teams: Vec<TeamId>| teams.iter().filter_map(|t| stage.teams.get(t)).map(|t| t.name.clone()).collect::<Vec<String>>().join(", ");
+    let (advanced, eliminated) = (stage.get_advanced_teams(), stage.get_eliminated_teams());
+    if !advanced.is_empty() {
+        println!("Advanced: {}", team_names(advanced));
+    }
+    if !eliminated.is_empty() {
+        println!("Eliminated: {}", team_names(eliminated));
+    }
+}
+
+fn print_schedule(stage: &Stage) {
+    let schedule = match &stage.kind {
+        StageKind::RoundRobin { schedule, .. } => schedule,
+        _ => return,
+    };
+    let team_name = |t: TeamId| stage.teams.get(&t).map(|t| t.name.clone()).unwrap_or("?".to_string());
+    let rows: Vec<Vec<String>> = schedule.iter().enumerate().map(|(i, m)| {
+        // The match might have its teams the other way round to the schedule
+        let score = m.match_id.and_then(|id| stage.matches.get(&id)).map(|x| {
+            if x.team_a == m.team_a { format!("{} - {}", x.team_a_score, x.team_b_score) } else { format!("{} - {}", x.team_b_score, x.team_a_score) }
+        }).unwrap_or_default();
+        vec![(i + 1).to_string(), stage.get_round_label(m.round), m.time.clone().unwrap_or_default(), team_name(m.team_a), score, team_name(m.team_b)]
+    }).collect();
+    print_text_table(&["Match", "Round", "Time", "Team A", "Score", "Team B"], &rows);
+}
+
+fn print_swiss(stage: &Stage) {
+    let pairings = match &stage.kind {
+        StageKind::Swiss { pairings, .. } => pairings,
+        _ => return,
+    };
+    let team_name = |t: TeamId| stage.teams.get(&t).map(|t| t.name.clone()).unwrap_or("?".to_string());
+    for (round, round_pairings) in pairings.iter().enumerate() {
+        println!("Round {}", round + 1);
+        let rows: Vec<Vec<String>> = round_pairings.iter().enumerate().map(|(i, p)| {
+            // The match might have its teams the other way round to the pairing
+            let score = p.match_id.and_then(|m| stage.matches.get(&m)).map(|m| {
+                if m.team_a == p.team_a { format!("{} - {}", m.team_a_score, m.team_b_score) } else { format!("{} - {}", m.team_b_score, m.team_a_score) }
+            }).unwrap_or_default();
+            vec![(i + 1).to_string(), team_name(p.team_a), score, team_name(p.team_b)]
+        }).collect();
+        print_text_table(&["Pairing", "Team A", "Score", "Team B"], &rows);
+        println!();
+    }
+
+    let rows: Vec<Vec<String>> = stage.get_standings().iter().map(|e| {
+        vec![team_name(e.team_id), format!("{} - {}", e.wins, e.losses), stage.get_buchholz(e.team_id).to_string(), stage.get_swiss_status(e.team_id).get_name().to_string()]
+    }).collect();
+    print_text_table(&["Team", "W - L", "Buchholz", "Status"], &rows);
+}
+
+/// Prints the given rows with each column padded to the same width.
+fn print_text_table(headings: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headings.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = std::cmp::max(widths[i], cell.chars().count());
+        }
+    }
+
+    let print_row = |cells: &[&str]| {
+        let padded: Vec<String> = cells.iter().zip(&widths).map(|(c, w)| format!("{c:w$}")).collect();
+        println!("{}", padded.join(" | ").trim_end());
+    };
+
+    print_row(headings);
+    println!("{}", widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<String>>().join("-+-"));
+    for row in rows {
+        print_row(&row.iter().map(|c| &c[..]).collect::<Vec<&str>>());
+    }
+}